@@ -1,63 +1,517 @@
-use std::{io::{self, Read}};
+use std::{io::{self, BufRead, Read}, env};
 
-fn part_one(input: &str) -> i32 {
+fn parse_depths(input: &str) -> Vec<i64> {
+    input
+        .lines()
+        .filter(|x| !x.trim().is_empty())
+        .map(|x| x.trim().parse::<i64>().unwrap())
+        .collect()
+}
+
+// Two neighbouring windows of size k share k - 1 readings, so comparing their
+// sums only needs the reading entering one window against the reading that
+// left it. The first window is never compared against anything, so no reading
+// or sum is treated as a "no previous value" marker.
+fn count_window_increases(values: &[i64], window: usize) -> i32 {
     let mut increases = 0;
-    let mut last_value = 0;
 
-    for line in input.lines() {
-        let value = line.trim().parse::<i32>().unwrap();
+    let mut index = window;
+    while index < values.len() {
+        if values[index] > values[index - window] {
+            increases += 1;
+        }
+        index += 1;
+    }
+
+    increases
+}
 
-        if last_value == 0 {
-            last_value = value;
+fn part_one(input: &str) -> i32 {
+    count_window_increases(&parse_depths(input), 1)
+}
+
+fn part_two(input: &str) -> i32 {
+    count_window_increases(&parse_depths(input), 3)
+}
+
+// Tracks window increases one reading at a time, keeping only the last
+// `window` readings in a ring buffer.
+struct SonarSweep {
+    window: usize,
+    recent: Vec<i64>,
+    next: usize,
+    readings: u64,
+    increases: u64,
+}
+
+impl SonarSweep {
+    fn new(window: usize) -> SonarSweep {
+        SonarSweep {
+            window,
+            recent: Vec::with_capacity(window),
+            next: 0,
+            readings: 0,
+            increases: 0,
         }
+    }
 
-        if value > last_value {
-            increases += 1;
+    fn push(&mut self, depth: i64) {
+        self.readings += 1;
+
+        if self.recent.len() < self.window {
+            self.recent.push(depth);
+            return;
+        }
+
+        if depth > self.recent[self.next] {
+            self.increases += 1;
         }
 
-        last_value = value;
+        self.recent[self.next] = depth;
+        self.next = (self.next + 1) % self.window;
     }
+}
 
-    increases
+// Feeds every reading from `reader` through each sweep, calling `on_reading`
+// after each one so callers can report progress without holding the input.
+fn stream_depths<R: BufRead>(mut reader: R, sweeps: &mut [SonarSweep], mut on_reading: impl FnMut(&[SonarSweep])) -> Result<(), String> {
+    let mut line = String::new();
+    let mut line_number = 0;
+
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line).map_err(|e| format!("Error reading input: {}", e))?;
+        if read == 0 {
+            return Ok(());
+        }
+
+        line_number += 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let depth: i64 = trimmed.parse().map_err(|_| format!("Could not parse depth on line {}: {}", line_number, trimmed))?;
+        for sweep in sweeps.iter_mut() {
+            sweep.push(depth);
+        }
+        on_reading(sweeps);
+    }
 }
 
-fn part_two(input: &str) -> i32 {
-    let mut increases = 0;
-    let mut last_sum = 0;
-    
-    let values: Vec<i32> = input
-        .lines()
-        .map(|x| x.trim().parse::<i32>().unwrap())
+#[derive(Debug, PartialEq)]
+struct Jump {
+    index: usize,
+    change: i128,
+}
+
+#[derive(Debug, PartialEq)]
+struct Anomaly {
+    index: usize,
+    depth: i64,
+    rolling_mean: f64,
+    deviations: f64,
+}
+
+#[derive(Debug, PartialEq)]
+struct DepthReport {
+    count: usize,
+    min: i64,
+    max: i64,
+    mean: f64,
+    longest_increasing_run: usize,
+    longest_decreasing_run: usize,
+    largest_jump: Option<Jump>,
+    anomalies: Vec<Anomaly>,
+}
+
+// Runs are counted in comparisons, so a run of 2 means three readings in a
+// row that each went deeper (or shallower) than the one before.
+fn longest_runs(depths: &[i64]) -> (usize, usize) {
+    let mut longest = (0, 0);
+    let mut current = (0, 0);
+
+    for pair in depths.windows(2) {
+        if pair[1] > pair[0] {
+            current = (current.0 + 1, 0);
+        } else if pair[1] < pair[0] {
+            current = (0, current.1 + 1);
+        } else {
+            current = (0, 0);
+        }
+
+        longest = (longest.0.max(current.0), longest.1.max(current.1));
+    }
+
+    longest
+}
+
+fn largest_jump(depths: &[i64]) -> Option<Jump> {
+    let mut largest: Option<Jump> = None;
+
+    for index in 1..depths.len() {
+        let change = depths[index] as i128 - depths[index - 1] as i128;
+        if largest.as_ref().is_none_or(|jump| change.abs() > jump.change.abs()) {
+            largest = Some(Jump { index, change });
+        }
+    }
+
+    largest
+}
+
+// A reading is anomalous when it sits more than `sigma` standard deviations
+// away from the mean of the `rolling` readings before it. Flat stretches have
+// no spread to measure against and are skipped.
+fn find_anomalies(depths: &[i64], rolling: usize, sigma: f64) -> Vec<Anomaly> {
+    let mut anomalies = Vec::<Anomaly>::new();
+
+    for index in rolling..depths.len() {
+        let previous = &depths[index - rolling..index];
+        let mean = previous.iter().map(|depth| *depth as i128).sum::<i128>() as f64 / rolling as f64;
+        let variance = previous.iter().map(|depth| (*depth as f64 - mean).powi(2)).sum::<f64>() / rolling as f64;
+        let deviation = variance.sqrt();
+
+        if deviation == 0.0 {
+            continue;
+        }
+
+        let deviations = (depths[index] as f64 - mean).abs() / deviation;
+        if deviations > sigma {
+            anomalies.push(Anomaly { index, depth: depths[index], rolling_mean: mean, deviations });
+        }
+    }
+
+    anomalies
+}
+
+fn report_depths(depths: &[i64], rolling: usize, sigma: f64) -> Option<DepthReport> {
+    let min = *depths.iter().min()?;
+    let max = *depths.iter().max()?;
+    let mean = depths.iter().map(|depth| *depth as i128).sum::<i128>() as f64 / depths.len() as f64;
+    let (longest_increasing_run, longest_decreasing_run) = longest_runs(depths);
+
+    Some(DepthReport {
+        count: depths.len(),
+        min,
+        max,
+        mean,
+        longest_increasing_run,
+        longest_decreasing_run,
+        largest_jump: largest_jump(depths),
+        anomalies: find_anomalies(depths, rolling, sigma),
+    })
+}
+
+impl DepthReport {
+    fn to_table(&self) -> String {
+        let mut rows = vec![
+            (String::from("readings"), self.count.to_string()),
+            (String::from("min"), self.min.to_string()),
+            (String::from("max"), self.max.to_string()),
+            (String::from("mean"), format!("{:.2}", self.mean)),
+            (String::from("longest increasing run"), self.longest_increasing_run.to_string()),
+            (String::from("longest decreasing run"), self.longest_decreasing_run.to_string()),
+        ];
+
+        match &self.largest_jump {
+            Some(jump) => rows.push((String::from("largest jump"), format!("{:+} at reading {}", jump.change, jump.index))),
+            None => rows.push((String::from("largest jump"), String::from("-"))),
+        }
+
+        rows.push((String::from("anomalies"), self.anomalies.len().to_string()));
+        for anomaly in &self.anomalies {
+            rows.push((
+                format!("  reading {}", anomaly.index),
+                format!("{} ({:.2} sigma from {:.2})", anomaly.depth, anomaly.deviations, anomaly.rolling_mean),
+            ));
+        }
+
+        let width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+        let mut table = String::new();
+        for (label, value) in rows {
+            table.push_str(&format!("{:<width$}  {}\n", label, value, width = width));
+        }
+
+        table
+    }
+
+    fn to_json(&self) -> String {
+        let largest_jump = match &self.largest_jump {
+            Some(jump) => format!("{{\"index\":{},\"change\":{}}}", jump.index, jump.change),
+            None => String::from("null"),
+        };
+
+        let anomalies: Vec<String> = self.anomalies
+            .iter()
+            .map(|anomaly| format!("{{\"index\":{},\"depth\":{},\"rolling_mean\":{},\"deviations\":{}}}", anomaly.index, anomaly.depth, anomaly.rolling_mean, anomaly.deviations))
+            .collect();
+
+        format!(
+            "{{\"count\":{},\"min\":{},\"max\":{},\"mean\":{},\"longest_increasing_run\":{},\"longest_decreasing_run\":{},\"largest_jump\":{},\"anomalies\":[{}]}}",
+            self.count, self.min, self.max, self.mean, self.longest_increasing_run, self.longest_decreasing_run, largest_jump, anomalies.join(",")
+        )
+    }
+}
+
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const SVG_WIDTH: f64 = 800.0;
+const SVG_CHART_HEIGHT: f64 = 300.0;
+const SVG_MARGIN: f64 = 60.0;
+const SVG_TRACK_HEIGHT: f64 = 20.0;
+const SVG_COLOURS: [&str; 4] = ["#d62728", "#ff7f0e", "#2ca02c", "#9467bd"];
+
+// Marks every reading that ends a window deeper than the window before it,
+// i.e. exactly the readings `count_window_increases` counts.
+fn window_increases(values: &[i64], window: usize) -> Vec<bool> {
+    (0..values.len())
+        .map(|index| index >= window && values[index] > values[index - window])
+        .collect()
+}
+
+// Squeezes the series into at most `width` columns. Each column shows the
+// deepest reading in its bucket, and a track is marked if any reading in the
+// bucket is an increase for that window.
+fn render_sparkline(depths: &[i64], windows: &[usize], width: usize) -> String {
+    let (min, max) = match (depths.iter().min(), depths.iter().max()) {
+        (Some(min), Some(max)) => (*min, *max),
+        _ => return String::from("No depth readings to plot\n"),
+    };
+
+    let columns = depths.len().min(width);
+    let buckets: Vec<(usize, usize)> = (0..columns)
+        .map(|column| (column * depths.len() / columns, (column + 1) * depths.len() / columns))
         .collect();
 
-    let mut index = 2;
-    while index < values.len() {
-        let sum = values[index - 2] + values[index - 1] + values[index];
-    
-        if last_sum == 0 {
-            last_sum = sum;
+    let labels: Vec<String> = windows.iter().map(|window| format!("window {}", window)).collect();
+    let label_width = labels.iter().map(|label| label.len()).max().unwrap_or(0).max("depth".len());
+
+    let mut chart = format!("depth {}..{} (taller is deeper)\n", min, max);
+
+    let mut line = format!("{:<width$} |", "depth", width = label_width);
+    for (start, end) in &buckets {
+        let deepest = *depths[*start..*end].iter().max().unwrap();
+        let level = if max == min {
+            0
+        } else {
+            ((deepest as i128 - min as i128) * (SPARK_LEVELS.len() as i128 - 1) / (max as i128 - min as i128)) as usize
+        };
+        line.push(SPARK_LEVELS[level]);
+    }
+    chart.push_str(line.trim_end());
+    chart.push('\n');
+
+    for (window, label) in windows.iter().zip(labels.iter()) {
+        let flags = window_increases(depths, *window);
+        let mut line = format!("{:<width$} |", label, width = label_width);
+        for (start, end) in &buckets {
+            line.push(if flags[*start..*end].iter().any(|flag| *flag) { '^' } else { ' ' });
         }
+        chart.push_str(line.trim_end());
+        chart.push('\n');
+    }
 
-        if sum > last_sum {
-            increases += 1;
+    chart
+}
+
+// Depth is drawn downward. Below the profile, each window gets a track with a
+// tick under every reading it counts as an increase.
+fn render_svg(depths: &[i64], windows: &[usize]) -> String {
+    let height = SVG_CHART_HEIGHT + SVG_MARGIN * 2.0 + SVG_TRACK_HEIGHT * windows.len() as f64;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+        SVG_WIDTH, height, SVG_WIDTH, height
+    );
+    svg.push_str(&format!("  <rect width=\"{}\" height=\"{}\" fill=\"white\"/>\n", SVG_WIDTH, height));
+
+    let (min, max) = match (depths.iter().min(), depths.iter().max()) {
+        (Some(min), Some(max)) => (*min, *max),
+        _ => {
+            svg.push_str("  <text x=\"10\" y=\"20\">No depth readings to plot</text>\n</svg>\n");
+            return svg;
         }
+    };
 
-        last_sum = sum;
-        index += 1;
+    let plot_width = SVG_WIDTH - SVG_MARGIN * 2.0;
+    let x = |index: usize| SVG_MARGIN + index as f64 * plot_width / (depths.len().max(2) - 1) as f64;
+    let y = |depth: i64| {
+        let fraction = if max == min { 0.5 } else { (depth as f64 - min as f64) / (max as f64 - min as f64) };
+        SVG_MARGIN + fraction * SVG_CHART_HEIGHT
+    };
+
+    svg.push_str(&format!("  <text x=\"{}\" y=\"{}\" font-size=\"12\">{}</text>\n", SVG_MARGIN, SVG_MARGIN - 10.0, min));
+    svg.push_str(&format!("  <text x=\"{}\" y=\"{}\" font-size=\"12\">{}</text>\n", SVG_MARGIN, SVG_MARGIN + SVG_CHART_HEIGHT + 15.0, max));
+
+    let points: Vec<String> = depths.iter().enumerate().map(|(index, depth)| format!("{:.2},{:.2}", x(index), y(*depth))).collect();
+    svg.push_str(&format!("  <polyline fill=\"none\" stroke=\"steelblue\" stroke-width=\"1.5\" points=\"{}\"/>\n", points.join(" ")));
+
+    for (track, window) in windows.iter().enumerate() {
+        let colour = SVG_COLOURS[track % SVG_COLOURS.len()];
+        let track_y = SVG_MARGIN + SVG_CHART_HEIGHT + SVG_TRACK_HEIGHT * (track as f64 + 1.0);
+        let flags = window_increases(depths, *window);
+        let count = flags.iter().filter(|flag| **flag).count();
+
+        svg.push_str(&format!(
+            "  <text x=\"5\" y=\"{:.2}\" font-size=\"10\" fill=\"{}\">window {}: {}</text>\n",
+            track_y + 4.0, colour, window, count
+        ));
+
+        for (index, _) in flags.iter().enumerate().filter(|(_, flag)| **flag) {
+            svg.push_str(&format!(
+                "  <line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke=\"{}\"/>\n",
+                x(index), track_y - 6.0, x(index), track_y + 6.0, colour
+            ));
+        }
     }
 
-    increases
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ReportFormat {
+    Table,
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PlotFormat {
+    Ascii,
+    Svg,
+}
+
+struct Options {
+    window: Option<usize>,
+    stream: bool,
+    report_every: Option<u64>,
+    report: Option<ReportFormat>,
+    rolling: usize,
+    sigma: f64,
+    plot: Option<PlotFormat>,
+    plot_width: usize,
+}
+
+fn parse_positive<T: std::str::FromStr + Default + PartialEq>(flag: &str, value: Option<String>) -> T {
+    let value = value.unwrap_or_else(|| panic!("Missing value for {}", flag));
+    match value.parse::<T>() {
+        Ok(parsed) if parsed != T::default() => parsed,
+        _ => panic!("{} must be a positive integer: {}", flag, value),
+    }
+}
+
+fn parse_options() -> Options {
+    let mut options = Options { window: None, stream: false, report_every: None, report: None, rolling: 10, sigma: 3.0, plot: None, plot_width: 80 };
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--window" => options.window = Some(parse_positive(&arg, args.next())),
+            "--stream" => options.stream = true,
+            "--report-every" => options.report_every = Some(parse_positive(&arg, args.next())),
+            "--report" => {
+                options.report = match args.next().as_deref() {
+                    Some("table") => Some(ReportFormat::Table),
+                    Some("json") => Some(ReportFormat::Json),
+                    other => panic!("--report must be table or json: {:?}", other),
+                }
+            },
+            "--rolling" => options.rolling = parse_positive(&arg, args.next()),
+            "--sigma" => {
+                let sigma = args.next().expect("Missing value for --sigma");
+                options.sigma = sigma.parse().expect("--sigma must be a number");
+            },
+            "--plot" => {
+                options.plot = match args.next().as_deref() {
+                    Some("ascii") => Some(PlotFormat::Ascii),
+                    Some("svg") => Some(PlotFormat::Svg),
+                    other => panic!("--plot must be ascii or svg: {:?}", other),
+                }
+            },
+            "--plot-width" => options.plot_width = parse_positive(&arg, args.next()),
+            _ => panic!("Unknown argument: {}", arg),
+        }
+    }
+
+    options
+}
+
+fn run_stream(options: &Options) {
+    let mut sweeps = vec![SonarSweep::new(1), SonarSweep::new(3)];
+    if let Some(window) = options.window {
+        sweeps.push(SonarSweep::new(window));
+    }
+
+    let stdin = io::stdin();
+    stream_depths(stdin.lock(), &mut sweeps, |sweeps| {
+        if let Some(every) = options.report_every {
+            if sweeps[0].readings % every == 0 {
+                let counts: Vec<String> = sweeps.iter().map(|sweep| format!("window {}: {}", sweep.window, sweep.increases)).collect();
+                println!("After {} readings: {}", sweeps[0].readings, counts.join(", "));
+            }
+        }
+    }).expect("Error streaming depths");
+
+    println!("Day one part one: {}", sweeps[0].increases);
+    println!("Day two part two: {}", sweeps[1].increases);
+    if let Some(sweep) = sweeps.get(2) {
+        println!("Day one window of {}: {}", sweep.window, sweep.increases);
+    }
 }
 
 fn main() {
+    let options = parse_options();
+
+    if options.stream {
+        if options.report.is_some() || options.plot.is_some() {
+            panic!("--stream cannot be combined with --report or --plot");
+        }
+
+        run_stream(&options);
+        return;
+    }
+
+    let json = options.report == Some(ReportFormat::Json);
+    let svg = options.plot == Some(PlotFormat::Svg);
+    if json && svg {
+        panic!("--report json and --plot svg cannot be combined");
+    }
+
     let mut buffer = String::new();
     io::stdin().read_to_string(&mut buffer).expect("Error reading input");
 
-    let increasing_measurements = part_one(&buffer);
-    println!("Day one part one: {}", increasing_measurements);
+    if !json && !svg {
+        let increasing_measurements = part_one(&buffer);
+        println!("Day one part one: {}", increasing_measurements);
 
-    let increasing_measurements = part_two(&buffer);
-    println!("Day two part two: {}", increasing_measurements);
+        let increasing_measurements = part_two(&buffer);
+        println!("Day two part two: {}", increasing_measurements);
+
+        if let Some(window) = options.window {
+            let increasing_measurements = count_window_increases(&parse_depths(&buffer), window);
+            println!("Day one window of {}: {}", window, increasing_measurements);
+        }
+    }
+
+    if let Some(format) = options.report {
+        match report_depths(&parse_depths(&buffer), options.rolling, options.sigma) {
+            Some(report) if format == ReportFormat::Json => println!("{}", report.to_json()),
+            Some(report) => print!("{}", report.to_table()),
+            None => println!("No depth readings to report on"),
+        }
+    }
+
+    if let Some(format) = options.plot {
+        let mut windows = vec![1, 3];
+        if let Some(window) = options.window {
+            windows.push(window);
+        }
+
+        let depths = parse_depths(&buffer);
+        match format {
+            PlotFormat::Ascii => print!("{}", render_sparkline(&depths, &windows, options.plot_width)),
+            PlotFormat::Svg => print!("{}", render_svg(&depths, &windows)),
+        }
+    }
 }
 
 #[test]
@@ -68,4 +522,190 @@ fn test_part_one() {
 #[test]
 fn test_part_two() {
     assert_eq!(5, part_two("199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n"));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_count_window_increases() {
+    let values = parse_depths("199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n");
+    assert_eq!(7, count_window_increases(&values, 1));
+    assert_eq!(5, count_window_increases(&values, 3));
+
+    // Windows of two: 399, 408, 418, 410, 407, 447, 509, 529, 523
+    assert_eq!(5, count_window_increases(&values, 2));
+
+    assert_eq!(1, count_window_increases(&values, 9));
+    assert_eq!(0, count_window_increases(&values, 10));
+    assert_eq!(0, count_window_increases(&values, 20));
+}
+
+#[test]
+fn test_zero_and_negative_depths() {
+    assert_eq!(2, part_one("0\n0\n1\n2\n"));
+    assert_eq!(3, part_one("-3\n-2\n-1\n0\n"));
+    assert_eq!(2, part_one("2\n0\n-1\n-5\n3\n4\n"));
+    assert_eq!(0, part_one("5\n0\n"));
+
+    // Window sums: 0, 0, 1, -5, -2
+    assert_eq!(2, part_two("1\n0\n-1\n1\n1\n-5\n2\n"));
+    // Window sums: -6, -3, 0, 3
+    assert_eq!(3, part_two("-3\n-2\n-1\n0\n1\n2\n"));
+}
+
+#[test]
+fn test_large_depths() {
+    let input = format!("{}\n{}\n{}\n", i64::from(i32::MAX) * 4, i64::from(i32::MAX) * 5, i64::from(i32::MIN) * 3);
+    assert_eq!(1, part_one(&input));
+}
+
+#[test]
+fn test_report_extreme_depths() {
+    let report = report_depths(&[i64::MAX - 1, i64::MAX - 2, 5], 2, 3.0).unwrap();
+    assert_eq!(5, report.min);
+    assert_eq!(i64::MAX - 1, report.max);
+    assert!((report.mean - (i64::MAX as f64 * 2.0 / 3.0)).abs() < 1e6);
+    assert_eq!(Some(Jump { index: 2, change: 5 - (i64::MAX as i128 - 2) }), report.largest_jump);
+
+    assert_eq!(Some(Jump { index: 1, change: u64::MAX as i128 }), largest_jump(&[i64::MIN, i64::MAX]));
+
+    let anomalies = find_anomalies(&[i64::MAX, i64::MAX / 2, i64::MAX, i64::MIN], 3, 3.0);
+    assert_eq!(1, anomalies.len());
+    assert_eq!(i64::MIN, anomalies[0].depth);
+}
+
+#[test]
+fn test_sonar_sweep() {
+    let mut sweep = SonarSweep::new(3);
+    for depth in [199, 200, 208, 210, 200, 207, 240, 269, 260, 263] {
+        sweep.push(depth);
+    }
+
+    assert_eq!(10, sweep.readings);
+    assert_eq!(5, sweep.increases);
+    assert_eq!(3, sweep.recent.len());
+}
+
+#[test]
+fn test_stream_depths() {
+    let input = "199\n200\n208\n210\n\n200\n207\n240\n269\n260\n263\n";
+    let mut sweeps = vec![SonarSweep::new(1), SonarSweep::new(3), SonarSweep::new(2)];
+    let mut progress = Vec::<u64>::new();
+
+    stream_depths(input.as_bytes(), &mut sweeps, |sweeps| progress.push(sweeps[0].increases)).unwrap();
+
+    assert_eq!(vec![0, 1, 2, 3, 3, 4, 5, 6, 6, 7], progress);
+    assert_eq!(7, sweeps[0].increases);
+    assert_eq!(5, sweeps[1].increases);
+    assert_eq!(5, sweeps[2].increases);
+
+    let mut sweeps = vec![SonarSweep::new(1)];
+    let error = stream_depths("1\n2\nthree\n".as_bytes(), &mut sweeps, |_| ()).unwrap_err();
+    assert!(error.contains("line 3"));
+}
+
+#[test]
+fn test_report_depths() {
+    let depths = parse_depths("199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n");
+    let report = report_depths(&depths, 10, 3.0).unwrap();
+
+    assert_eq!(10, report.count);
+    assert_eq!(199, report.min);
+    assert_eq!(269, report.max);
+    assert_eq!(225.6, report.mean);
+    assert_eq!(3, report.longest_increasing_run);
+    assert_eq!(1, report.longest_decreasing_run);
+    assert_eq!(Some(Jump { index: 6, change: 33 }), report.largest_jump);
+    assert!(report.anomalies.is_empty());
+
+    assert_eq!(None, report_depths(&[], 10, 3.0));
+}
+
+#[test]
+fn test_longest_runs() {
+    assert_eq!((0, 0), longest_runs(&[5]));
+    assert_eq!((2, 3), longest_runs(&[1, 2, 3, 3, 2, 1, 0, 0, 1]));
+    assert_eq!((1, 1), longest_runs(&[-1, 0, -1, 0]));
+}
+
+#[test]
+fn test_find_anomalies() {
+    let depths = [100, 102, 98, 101, 99, 100, 150, 101, 100, 100, 100, 100];
+    let anomalies = find_anomalies(&depths, 4, 3.0);
+
+    assert_eq!(1, anomalies.len());
+    assert_eq!(6, anomalies[0].index);
+    assert_eq!(150, anomalies[0].depth);
+    assert_eq!(99.5, anomalies[0].rolling_mean);
+
+    // The flat tail has no spread, so it is never flagged.
+    assert!(find_anomalies(&[5, 5, 5, 5, 9], 4, 1.0).is_empty());
+}
+
+#[test]
+fn test_report_output() {
+    let report = report_depths(&[3, 1, 4], 2, 3.0).unwrap();
+
+    let table = report.to_table();
+    assert!(table.starts_with("readings                3\n"));
+    assert!(table.contains("largest jump            +3 at reading 2\n"));
+
+    assert_eq!(
+        "{\"count\":3,\"min\":1,\"max\":4,\"mean\":2.6666666666666665,\"longest_increasing_run\":1,\"longest_decreasing_run\":1,\"largest_jump\":{\"index\":2,\"change\":3},\"anomalies\":[]}",
+        report.to_json()
+    );
+}
+
+#[test]
+fn test_window_increases() {
+    let depths = parse_depths("199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n");
+
+    let flags = window_increases(&depths, 1);
+    assert_eq!(vec![false, true, true, true, false, true, true, true, false, true], flags);
+    assert_eq!(part_one("199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n") as usize, flags.iter().filter(|flag| **flag).count());
+
+    let flags = window_increases(&depths, 3);
+    assert_eq!(5, flags.iter().filter(|flag| **flag).count());
+}
+
+#[test]
+fn test_render_sparkline() {
+    let depths = parse_depths("199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n");
+    let chart = render_sparkline(&depths, &[1, 3], 80);
+
+    assert_eq!("depth 199..269 (taller is deeper)\n\
+        depth    |▁▁▁▂▁▁▅█▇▇\n\
+        window 1 | ^^^ ^^^ ^\n\
+        window 3 |   ^  ^^^^\n", chart);
+
+    let chart = render_sparkline(&depths, &[1], 5);
+    assert_eq!("depth 199..269 (taller is deeper)\ndepth    |▁▂▁█▇\nwindow 1 |^^^^^\n", chart);
+
+    assert_eq!("No depth readings to plot\n", render_sparkline(&[], &[1], 80));
+}
+
+#[test]
+fn test_render_svg() {
+    let depths = parse_depths("199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n");
+    let svg = render_svg(&depths, &[1, 3]);
+
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    assert_eq!(1, svg.matches("<polyline").count());
+    assert_eq!(7 + 5, svg.matches("<line").count());
+    assert!(svg.contains("window 1: 7"));
+    assert!(svg.contains("window 3: 5"));
+
+    // The shallowest reading sits at the top of the chart, the deepest at the bottom.
+    assert!(svg.contains("60.00,60.00 "));
+    assert!(svg.contains(",360.00 "));
+}
+
+#[test]
+fn test_plot_extreme_depths() {
+    let depths = [i64::MIN, 0, i64::MAX];
+
+    let chart = render_sparkline(&depths, &[1], 80);
+    assert_eq!(format!("depth {}..{} (taller is deeper)\ndepth    |▁▄█\nwindow 1 | ^^\n", i64::MIN, i64::MAX), chart);
+
+    let svg = render_svg(&depths, &[1]);
+    assert!(svg.contains("points=\"60.00,60.00 400.00,210.00 740.00,360.00\""));
+}
@@ -0,0 +1,363 @@
+use std::io::{self, Read};
+use std::str::FromStr;
+use std::ops::Add;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum SnailfishNumber {
+    Regular(u32),
+    Pair(Box<SnailfishNumber>, Box<SnailfishNumber>),
+}
+
+impl FromStr for SnailfishNumber {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.trim().chars().collect();
+        let mut index = 0;
+        let number = SnailfishNumber::parse_chars(&chars, &mut index)?;
+
+        if index != chars.len() {
+            return Err(format!("Unexpected trailing input in snailfish number: {}", s));
+        }
+
+        Ok(number)
+    }
+}
+
+impl fmt::Display for SnailfishNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnailfishNumber::Regular(value) => write!(f, "{}", value),
+            SnailfishNumber::Pair(left, right) => write!(f, "[{},{}]", left, right),
+        }
+    }
+}
+
+impl Add for SnailfishNumber {
+    type Output = SnailfishNumber;
+    fn add(self, other: SnailfishNumber) -> SnailfishNumber {
+        let mut result = SnailfishNumber::Pair(Box::new(self), Box::new(other));
+        result.reduce();
+
+        result
+    }
+}
+
+impl SnailfishNumber {
+    fn parse_chars(chars: &[char], index: &mut usize) -> Result<Self, String> {
+        match chars.get(*index) {
+            Some('[') => {
+                *index += 1;
+                let left = SnailfishNumber::parse_chars(chars, index)?;
+                SnailfishNumber::expect_char(chars, index, ',')?;
+                let right = SnailfishNumber::parse_chars(chars, index)?;
+                SnailfishNumber::expect_char(chars, index, ']')?;
+
+                Ok(SnailfishNumber::Pair(Box::new(left), Box::new(right)))
+            },
+            Some(c) if c.is_ascii_digit() => {
+                let mut value = 0;
+                while let Some(digit) = chars.get(*index).and_then(|c| c.to_digit(10)) {
+                    value = value * 10 + digit;
+                    *index += 1;
+                }
+
+                Ok(SnailfishNumber::Regular(value))
+            },
+            Some(c) => Err(format!("Unexpected character '{}' at position {}", c, index)),
+            None => Err(String::from("Unexpected end of snailfish number")),
+        }
+    }
+
+    fn expect_char(chars: &[char], index: &mut usize, expected: char) -> Result<(), String> {
+        match chars.get(*index) {
+            Some(&c) if c == expected => {
+                *index += 1;
+                Ok(())
+            },
+            Some(c) => Err(format!("Expected '{}' but found '{}' at position {}", expected, c, index)),
+            None => Err(format!("Expected '{}' but reached end of input", expected)),
+        }
+    }
+
+    fn reduce(&mut self) {
+        loop {
+            if self.explode() {
+                continue;
+            }
+
+            if self.split() {
+                continue;
+            }
+
+            break;
+        }
+    }
+
+    fn explode(&mut self) -> bool {
+        self.explode_at_depth(0).is_some()
+    }
+
+    // Returns the values still to be carried to the left and right neighbours
+    // when a pair somewhere below this node has exploded.
+    fn explode_at_depth(&mut self, depth: usize) -> Option<(Option<u32>, Option<u32>)> {
+        let (left, right) = match self {
+            SnailfishNumber::Regular(_) => return None,
+            SnailfishNumber::Pair(left, right) => (left, right),
+        };
+
+        if depth >= 4 {
+            if let (SnailfishNumber::Regular(left_value), SnailfishNumber::Regular(right_value)) = (&**left, &**right) {
+                let carry = (Some(*left_value), Some(*right_value));
+                *self = SnailfishNumber::Regular(0);
+                return Some(carry);
+            }
+        }
+
+        if let Some((carry_left, carry_right)) = left.explode_at_depth(depth + 1) {
+            if let Some(value) = carry_right {
+                right.add_to_leftmost(value);
+            }
+            return Some((carry_left, None));
+        }
+
+        if let Some((carry_left, carry_right)) = right.explode_at_depth(depth + 1) {
+            if let Some(value) = carry_left {
+                left.add_to_rightmost(value);
+            }
+            return Some((None, carry_right));
+        }
+
+        None
+    }
+
+    fn add_to_leftmost(&mut self, amount: u32) {
+        match self {
+            SnailfishNumber::Regular(value) => *value += amount,
+            SnailfishNumber::Pair(left, _) => left.add_to_leftmost(amount),
+        }
+    }
+
+    fn add_to_rightmost(&mut self, amount: u32) {
+        match self {
+            SnailfishNumber::Regular(value) => *value += amount,
+            SnailfishNumber::Pair(_, right) => right.add_to_rightmost(amount),
+        }
+    }
+
+    fn split(&mut self) -> bool {
+        match self {
+            SnailfishNumber::Regular(value) if *value >= 10 => {
+                let left = SnailfishNumber::Regular(*value / 2);
+                let right = SnailfishNumber::Regular(value.div_ceil(2));
+                *self = SnailfishNumber::Pair(Box::new(left), Box::new(right));
+                true
+            },
+            SnailfishNumber::Regular(_) => false,
+            SnailfishNumber::Pair(left, right) => left.split() || right.split(),
+        }
+    }
+
+    fn magnitude(&self) -> u64 {
+        match self {
+            SnailfishNumber::Regular(value) => *value as u64,
+            SnailfishNumber::Pair(left, right) => 3 * left.magnitude() + 2 * right.magnitude(),
+        }
+    }
+}
+
+fn parse_numbers(input: &str) -> Vec<SnailfishNumber> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.parse().expect("Error parsing snailfish number"))
+        .collect()
+}
+
+fn sum_numbers(numbers: Vec<SnailfishNumber>) -> SnailfishNumber {
+    numbers
+        .into_iter()
+        .reduce(|sum, number| sum + number)
+        .expect("Cannot sum an empty list of snailfish numbers")
+}
+
+fn largest_pairwise_magnitude(numbers: &[SnailfishNumber]) -> u64 {
+    let mut largest = 0;
+
+    for (i, left) in numbers.iter().enumerate() {
+        for (j, right) in numbers.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+
+            let magnitude = (left.clone() + right.clone()).magnitude();
+            if magnitude > largest {
+                largest = magnitude;
+            }
+        }
+    }
+
+    largest
+}
+
+fn part_one(input: &str) -> u64 {
+    let numbers = parse_numbers(input);
+
+    sum_numbers(numbers).magnitude()
+}
+
+fn part_two(input: &str) -> u64 {
+    let numbers = parse_numbers(input);
+
+    largest_pairwise_magnitude(&numbers)
+}
+
+fn main() {
+    let mut buffer = String::new();
+    io::stdin().read_to_string(&mut buffer).expect("Error reading input");
+
+    let result = part_one(&buffer);
+    println!("Day 18 part one: {}", result);
+
+    let result = part_two(&buffer);
+    println!("Day 18 part two: {}", result);
+}
+
+#[test]
+fn test_parse_and_display() {
+    let inputs = vec!["[1,2]", "[[1,2],3]", "[9,[8,7]]", "[[1,9],[8,5]]", "[[[[1,2],[3,4]],[[5,6],[7,8]]],9]", "[[[9,[3,8]],[[0,9],6]],[[[3,7],[4,9]],3]]"];
+
+    for input in inputs {
+        let number: SnailfishNumber = input.parse().unwrap();
+        assert_eq!(input, number.to_string());
+    }
+
+    assert!("[1,2".parse::<SnailfishNumber>().is_err());
+    assert!("[1;2]".parse::<SnailfishNumber>().is_err());
+    assert!("[1,2]]".parse::<SnailfishNumber>().is_err());
+}
+
+#[test]
+fn test_explode() {
+    let examples = vec![
+        ("[[[[[9,8],1],2],3],4]", "[[[[0,9],2],3],4]"),
+        ("[7,[6,[5,[4,[3,2]]]]]", "[7,[6,[5,[7,0]]]]"),
+        ("[[6,[5,[4,[3,2]]]],1]", "[[6,[5,[7,0]]],3]"),
+        ("[[3,[2,[1,[7,3]]]],[6,[5,[4,[3,2]]]]]", "[[3,[2,[8,0]]],[9,[5,[4,[3,2]]]]]"),
+        ("[[3,[2,[8,0]]],[9,[5,[4,[3,2]]]]]", "[[3,[2,[8,0]]],[9,[5,[7,0]]]]"),
+    ];
+
+    for (input, expected) in examples {
+        let mut number: SnailfishNumber = input.parse().unwrap();
+        assert!(number.explode());
+        assert_eq!(expected, number.to_string());
+    }
+
+    let mut number: SnailfishNumber = "[[1,2],3]".parse().unwrap();
+    assert!(!number.explode());
+}
+
+#[test]
+fn test_split() {
+    let mut number = SnailfishNumber::Regular(10);
+    assert!(number.split());
+    assert_eq!("[5,5]", number.to_string());
+
+    let mut number = SnailfishNumber::Regular(11);
+    assert!(number.split());
+    assert_eq!("[5,6]", number.to_string());
+
+    let mut number: SnailfishNumber = "[[1,2],9]".parse().unwrap();
+    assert!(!number.split());
+}
+
+#[test]
+fn test_reduction_steps() {
+    let mut number = SnailfishNumber::Pair(
+        Box::new("[[[[4,3],4],4],[7,[[8,4],9]]]".parse().unwrap()),
+        Box::new("[1,1]".parse().unwrap()),
+    );
+    assert_eq!("[[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]", number.to_string());
+
+    assert!(number.explode());
+    assert_eq!("[[[[0,7],4],[7,[[8,4],9]]],[1,1]]", number.to_string());
+
+    assert!(number.explode());
+    assert_eq!("[[[[0,7],4],[15,[0,13]]],[1,1]]", number.to_string());
+
+    assert!(!number.explode());
+    assert!(number.split());
+    assert_eq!("[[[[0,7],4],[[7,8],[0,13]]],[1,1]]", number.to_string());
+
+    assert!(!number.explode());
+    assert!(number.split());
+    assert_eq!("[[[[0,7],4],[[7,8],[0,[6,7]]]],[1,1]]", number.to_string());
+
+    assert!(number.explode());
+    assert_eq!("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]", number.to_string());
+
+    assert!(!number.explode());
+    assert!(!number.split());
+}
+
+#[test]
+fn test_add() {
+    let left: SnailfishNumber = "[[[[4,3],4],4],[7,[[8,4],9]]]".parse().unwrap();
+    let right: SnailfishNumber = "[1,1]".parse().unwrap();
+    assert_eq!("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]", (left + right).to_string());
+}
+
+#[test]
+fn test_sum_numbers() {
+    let numbers = parse_numbers("[1,1]\n[2,2]\n[3,3]\n[4,4]\n");
+    assert_eq!("[[[[1,1],[2,2]],[3,3]],[4,4]]", sum_numbers(numbers).to_string());
+
+    let numbers = parse_numbers("[1,1]\n[2,2]\n[3,3]\n[4,4]\n[5,5]\n");
+    assert_eq!("[[[[3,0],[5,3]],[4,4]],[5,5]]", sum_numbers(numbers).to_string());
+
+    let numbers = parse_numbers("[1,1]\n[2,2]\n[3,3]\n[4,4]\n[5,5]\n[6,6]\n");
+    assert_eq!("[[[[5,0],[7,4]],[5,5]],[6,6]]", sum_numbers(numbers).to_string());
+}
+
+#[test]
+fn test_magnitude() {
+    let number: SnailfishNumber = "[[1,2],[[3,4],5]]".parse().unwrap();
+    assert_eq!(143, number.magnitude());
+
+    let number: SnailfishNumber = "[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]".parse().unwrap();
+    assert_eq!(3488, number.magnitude());
+}
+
+#[test]
+fn test_part_one() {
+    let input = String::from("[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]
+    [[[5,[2,8]],4],[5,[[9,9],0]]]
+    [6,[[[6,2],[5,6]],[[7,6],[4,7]]]]
+    [[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]
+    [[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]
+    [[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]
+    [[[[5,4],[7,7]],8],[[8,3],8]]
+    [[9,3],[[9,9],[6,[4,9]]]]
+    [[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]
+    [[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]");
+
+    let sum = sum_numbers(parse_numbers(&input));
+    assert_eq!("[[[[6,6],[7,6]],[[7,7],[7,0]]],[[[7,7],[7,7]],[[7,8],[9,9]]]]", sum.to_string());
+    assert_eq!(4140, part_one(&input));
+}
+
+#[test]
+fn test_part_two() {
+    let input = String::from("[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]
+    [[[5,[2,8]],4],[5,[[9,9],0]]]
+    [6,[[[6,2],[5,6]],[[7,6],[4,7]]]]
+    [[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]
+    [[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]
+    [[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]
+    [[[[5,4],[7,7]],8],[[8,3],8]]
+    [[9,3],[[9,9],[6,[4,9]]]]
+    [[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]
+    [[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]");
+
+    assert_eq!(3993, part_two(&input));
+}
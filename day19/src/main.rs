@@ -0,0 +1,503 @@
+use std::io::{self, Read};
+use std::str::FromStr;
+use std::ops::{Add, Sub};
+use std::collections::{HashMap, HashSet};
+
+const ROTATIONS: usize = 24;
+const REQUIRED_OVERLAP: usize = 12;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Point {
+    x: i32,
+    y: i32,
+    z: i32,
+}
+
+impl FromStr for Point {
+    type Err = String;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut tokens = input.split(',');
+        let x: i32 = tokens.next().unwrap().trim().parse().map_err(|_| format!("Failed to parse x coordinate: {}", input))?;
+        let y: i32 = tokens.next().ok_or(format!("Missing y coordinate: {}", input))?.trim().parse().map_err(|_| format!("Failed to parse y coordinate: {}", input))?;
+        let z: i32 = tokens.next().ok_or(format!("Missing z coordinate: {}", input))?.trim().parse().map_err(|_| format!("Failed to parse z coordinate: {}", input))?;
+
+        if tokens.next().is_some() {
+            return Err(format!("Too many coordinates: {}", input));
+        }
+
+        Ok (Point { x, y, z })
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+    fn add(self, other: Point) -> Point {
+        Point { x: self.x + other.x, y: self.y + other.y, z: self.z + other.z }
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+    fn sub(self, other: Point) -> Point {
+        Point { x: self.x - other.x, y: self.y - other.y, z: self.z - other.z }
+    }
+}
+
+impl Point {
+    fn origin() -> Point {
+        Point { x: 0, y: 0, z: 0 }
+    }
+
+    // Rotations 0..24 are a spin about the x axis followed by turning the
+    // x axis to face one of the six directions.
+    fn rotate(&self, rotation: usize) -> Point {
+        let Point { x, y, z } = *self;
+        let (x, y, z) = match rotation % 4 {
+            0 => (x, y, z),
+            1 => (x, -z, y),
+            2 => (x, -y, -z),
+            _ => (x, z, -y),
+        };
+
+        let (x, y, z) = match rotation / 4 {
+            0 => (x, y, z),
+            1 => (-x, -y, z),
+            2 => (y, -x, z),
+            3 => (-y, x, z),
+            4 => (z, y, -x),
+            5 => (-z, y, x),
+            _ => panic!("Invalid rotation: {}", rotation),
+        };
+
+        Point { x, y, z }
+    }
+
+    fn manhattan_distance(&self, other: &Point) -> i32 {
+        (self.x - other.x).abs() + (self.y - other.y).abs() + (self.z - other.z).abs()
+    }
+}
+
+struct Scanner {
+    id: usize,
+    beacons: Vec<Point>,
+}
+
+impl FromStr for Scanner {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().map(|line| line.trim()).filter(|line| !line.is_empty());
+
+        let header = lines.next().ok_or("Missing scanner header")?;
+        let id = header
+            .trim_matches('-')
+            .trim()
+            .strip_prefix("scanner")
+            .ok_or(format!("Could not parse scanner header: {}", header))?;
+        let id: usize = id.trim().parse().map_err(|_| format!("Could not parse scanner id: {}", header))?;
+
+        let mut beacons = Vec::<Point>::new();
+        for line in lines {
+            beacons.push(line.parse()?);
+        }
+
+        Ok(Scanner { id, beacons })
+    }
+}
+
+impl Scanner {
+    fn rotated_beacons(&self, rotation: usize) -> Vec<Point> {
+        self.beacons.iter().map(|beacon| beacon.rotate(rotation)).collect()
+    }
+
+    // Finds the rotation and position of this scanner relative to a set of
+    // already aligned beacons, if at least twelve of them match up.
+    fn find_alignment(&self, known_beacons: &[Point]) -> Option<(usize, Point)> {
+        for rotation in 0..ROTATIONS {
+            let rotated = self.rotated_beacons(rotation);
+            let mut offsets = HashMap::<Point, usize>::new();
+
+            for known in known_beacons {
+                for beacon in &rotated {
+                    let count = offsets.entry(*known - *beacon).or_insert(0);
+                    *count += 1;
+
+                    if *count >= REQUIRED_OVERLAP {
+                        return Some((rotation, *known - *beacon));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+struct AlignedScanner {
+    id: usize,
+    position: Point,
+    beacons: Vec<Point>,
+}
+
+struct BeaconMap {
+    scanners: Vec<AlignedScanner>,
+}
+
+impl BeaconMap {
+    fn assemble(scanners: &[Scanner]) -> Result<Self, String> {
+        let first = scanners.first().ok_or("Cannot assemble a map without scanners")?;
+        let mut aligned = vec![AlignedScanner { id: first.id, position: Point::origin(), beacons: first.beacons.clone() }];
+        let mut remaining: Vec<&Scanner> = scanners.iter().skip(1).collect();
+
+        // Each aligned scanner only needs to be compared against the
+        // unaligned ones once, so walk the aligned list as a queue.
+        let mut next_reference = 0;
+        while !remaining.is_empty() && next_reference < aligned.len() {
+            let mut still_remaining = Vec::<&Scanner>::new();
+
+            for scanner in remaining {
+                match scanner.find_alignment(&aligned[next_reference].beacons) {
+                    Some((rotation, position)) => {
+                        let beacons = scanner.rotated_beacons(rotation).into_iter().map(|beacon| beacon + position).collect();
+                        aligned.push(AlignedScanner { id: scanner.id, position, beacons });
+                    },
+                    None => still_remaining.push(scanner),
+                }
+            }
+
+            remaining = still_remaining;
+            next_reference += 1;
+        }
+
+        if !remaining.is_empty() {
+            let ids: Vec<String> = remaining.iter().map(|scanner| scanner.id.to_string()).collect();
+            return Err(format!("Could not align scanners: {}", ids.join(", ")));
+        }
+
+        aligned.sort_by_key(|scanner| scanner.id);
+        Ok(BeaconMap { scanners: aligned })
+    }
+
+    fn beacons(&self) -> HashSet<Point> {
+        self.scanners.iter().flat_map(|scanner| scanner.beacons.iter().copied()).collect()
+    }
+
+    fn largest_scanner_distance(&self) -> i32 {
+        let mut largest = 0;
+
+        for first in &self.scanners {
+            for second in &self.scanners {
+                let distance = first.position.manhattan_distance(&second.position);
+                if distance > largest {
+                    largest = distance;
+                }
+            }
+        }
+
+        largest
+    }
+}
+
+fn parse_scanners(input: &str) -> Vec<Scanner> {
+    let mut reports = Vec::<String>::new();
+
+    for line in input.lines() {
+        let line = line.trim();
+        if line.starts_with("---") {
+            reports.push(String::new());
+        }
+
+        match reports.last_mut() {
+            Some(report) => {
+                report.push_str(line);
+                report.push('\n');
+            },
+            None if line.is_empty() => (),
+            None => panic!("Beacon found before any scanner header: {}", line),
+        }
+    }
+
+    reports.iter().map(|report| report.parse().expect("Error parsing scanner")).collect()
+}
+
+// Assembling the map is the expensive step, so both parts share one.
+fn part_one(map: &BeaconMap) -> usize {
+    map.beacons().len()
+}
+
+fn part_two(map: &BeaconMap) -> i32 {
+    map.largest_scanner_distance()
+}
+
+fn main() {
+    let mut buffer = String::new();
+    io::stdin().read_to_string(&mut buffer).expect("Error reading input");
+
+    let scanners = parse_scanners(&buffer);
+    let map = BeaconMap::assemble(&scanners).expect("Error assembling beacon map");
+
+    let result = part_one(&map);
+    println!("Day 19 part one: {}", result);
+
+    let result = part_two(&map);
+    println!("Day 19 part two: {}", result);
+}
+
+#[test]
+fn test_rotations_are_distinct() {
+    let point = Point { x: 1, y: 2, z: 3 };
+    let rotations: HashSet<Point> = (0..ROTATIONS).map(|rotation| point.rotate(rotation)).collect();
+    assert_eq!(24, rotations.len());
+
+    for rotation in 0..ROTATIONS {
+        let rotated = point.rotate(rotation);
+        assert_eq!(6, rotated.manhattan_distance(&Point::origin()));
+    }
+}
+
+#[test]
+fn test_parse_scanners() {
+    let input = String::from("--- scanner 0 ---
+    0,2,0
+    4,1,0
+    3,3,0
+
+    --- scanner 1 ---
+    -1,-1,0
+    -5,0,0
+    -2,1,0");
+
+    let scanners = parse_scanners(&input);
+    assert_eq!(2, scanners.len());
+    assert_eq!(1, scanners[1].id);
+    assert_eq!(3, scanners[1].beacons.len());
+    assert_eq!(Point { x: -5, y: 0, z: 0 }, scanners[1].beacons[1]);
+
+    assert!("1,2".parse::<Point>().is_err());
+    assert!("1,x,3".parse::<Point>().is_err());
+    assert!("1,2,3,4".parse::<Point>().is_err());
+    assert_eq!(Err(String::from("Failed to parse y coordinate: 0,oops,0")), "--- scanner 0 ---\n0,oops,0\n".parse::<Scanner>().map(|scanner| scanner.id));
+}
+
+#[test]
+fn test_orientations() {
+    let input = String::from("--- scanner 0 ---
+    -1,-1,1
+    -2,-2,2
+    -3,-3,3
+    -2,-3,1
+    5,6,-4
+    8,0,7
+
+    --- scanner 0 ---
+    1,-1,1
+    2,-2,2
+    3,-3,3
+    2,-1,3
+    -5,4,-6
+    -8,-7,0
+
+    --- scanner 0 ---
+    -1,-1,-1
+    -2,-2,-2
+    -3,-3,-3
+    -1,-3,-2
+    4,6,5
+    -7,0,8
+
+    --- scanner 0 ---
+    1,1,-1
+    2,2,-2
+    3,3,-3
+    1,3,-2
+    -4,-6,5
+    7,0,8
+
+    --- scanner 0 ---
+    1,1,1
+    2,2,2
+    3,3,3
+    3,1,2
+    -6,-4,-5
+    0,7,-8");
+
+    let scanners = parse_scanners(&input);
+    let reference: HashSet<Point> = scanners[0].beacons.iter().copied().collect();
+
+    for scanner in &scanners {
+        let matches = (0..ROTATIONS).any(|rotation| {
+            let rotated: HashSet<Point> = scanner.rotated_beacons(rotation).into_iter().collect();
+            rotated == reference
+        });
+        assert!(matches);
+    }
+}
+
+#[test]
+fn test_assemble_example_map() {
+    let input = String::from("--- scanner 0 ---
+    404,-588,-901
+    528,-643,409
+    -838,591,734
+    390,-675,-793
+    -537,-823,-458
+    -485,-357,347
+    -345,-311,381
+    -661,-816,-575
+    -876,649,763
+    -618,-824,-621
+    553,345,-567
+    474,580,667
+    -447,-329,318
+    -584,868,-557
+    544,-627,-890
+    564,392,-477
+    455,729,728
+    -892,524,684
+    -689,845,-530
+    423,-701,434
+    7,-33,-71
+    630,319,-379
+    443,580,662
+    -789,900,-551
+    459,-707,401
+
+    --- scanner 1 ---
+    686,422,578
+    605,423,415
+    515,917,-361
+    -336,658,858
+    95,138,22
+    -476,619,847
+    -340,-569,-846
+    567,-361,727
+    -460,603,-452
+    669,-402,600
+    729,430,532
+    -500,-761,534
+    -322,571,750
+    -466,-666,-811
+    -429,-592,574
+    -355,545,-477
+    703,-491,-529
+    -328,-685,520
+    413,935,-424
+    -391,539,-444
+    586,-435,557
+    -364,-763,-893
+    807,-499,-711
+    755,-354,-619
+    553,889,-390
+
+    --- scanner 2 ---
+    649,640,665
+    682,-795,504
+    -784,533,-524
+    -644,584,-595
+    -588,-843,648
+    -30,6,44
+    -674,560,763
+    500,723,-460
+    609,671,-379
+    -555,-800,653
+    -675,-892,-343
+    697,-426,-610
+    578,704,681
+    493,664,-388
+    -671,-858,530
+    -667,343,800
+    571,-461,-707
+    -138,-166,112
+    -889,563,-600
+    646,-828,498
+    640,759,510
+    -630,509,768
+    -681,-892,-333
+    673,-379,-804
+    -742,-814,-386
+    577,-820,562
+
+    --- scanner 3 ---
+    -589,542,597
+    605,-692,669
+    -500,565,-823
+    -660,373,557
+    -458,-679,-417
+    -488,449,543
+    -626,468,-788
+    338,-750,-386
+    528,-832,-391
+    562,-778,733
+    -938,-730,414
+    543,643,-506
+    -524,371,-870
+    407,773,750
+    -104,29,83
+    378,-903,-323
+    -778,-728,485
+    426,699,580
+    -438,-605,-362
+    -469,-447,-387
+    509,732,623
+    647,635,-688
+    -868,-804,481
+    614,-800,639
+    595,780,-596
+
+    --- scanner 4 ---
+    727,592,562
+    -293,-554,779
+    441,611,-461
+    -714,465,-776
+    -743,427,-804
+    -660,-479,-426
+    832,-632,460
+    927,-485,-438
+    408,393,-506
+    466,436,-512
+    110,16,151
+    -258,-428,682
+    -393,719,612
+    -211,-452,876
+    808,-476,-593
+    -575,615,604
+    -485,667,467
+    -680,325,-822
+    -627,-443,-432
+    872,-547,-609
+    833,512,582
+    807,604,487
+    839,-516,451
+    891,-625,532
+    -652,-548,-490
+    30,-46,-14");
+
+    let scanners = parse_scanners(&input);
+    let map = BeaconMap::assemble(&scanners).unwrap();
+
+    let positions = [
+        Point::origin(),
+        Point { x: 68, y: -1246, z: -43 },
+        Point { x: 1105, y: -1205, z: 1229 },
+        Point { x: -92, y: -2380, z: -20 },
+        Point { x: -20, y: -1133, z: 1061 },
+    ];
+    for (scanner, position) in map.scanners.iter().zip(positions.iter()) {
+        assert_eq!(*position, scanner.position);
+    }
+
+    assert_eq!(79, part_one(&map));
+    assert_eq!(3621, part_two(&map));
+}
+
+#[test]
+fn test_unalignable_scanner() {
+    let input = String::from("--- scanner 0 ---
+    0,0,0
+    1,1,1
+
+    --- scanner 1 ---
+    5,5,5");
+
+    let scanners = parse_scanners(&input);
+    assert!(BeaconMap::assemble(&scanners).is_err());
+}
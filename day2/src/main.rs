@@ -1,14 +1,129 @@
 use std::io::{self, Read};
+use std::str::FromStr;
+use std::env;
+use std::mem;
+use std::fmt;
+
+const SVG_WIDTH: f64 = 800.0;
+const SVG_HEIGHT: f64 = 400.0;
+const SVG_MARGIN: f64 = 60.0;
+const SVG_LEGEND_HEIGHT: f64 = 20.0;
+const SVG_COLOURS: [&str; 2] = ["steelblue", "#d62728"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Command {
+    Forward(i64),
+    Up(i64),
+    Down(i64),
+}
+
+impl FromStr for Command {
+    type Err = String;
+    fn from_str(cmd: &str) -> Result<Self, Self::Err> {
+        let mut tokens = cmd.split_whitespace();
+        let action = tokens.next().ok_or(format!("Could not parse command: {}", cmd))?;
+        let adjustment = tokens.next().ok_or(format!("Could not read adjustment: {}", cmd))?;
+        let adjustment = adjustment.parse::<i64>().map_err(|_| format!("Could not parse adjustment: {}", cmd))?;
+
+        match action.to_lowercase().as_str() {
+            "forward" => Ok(Command::Forward(adjustment)),
+            "up" => Ok(Command::Up(adjustment)),
+            "down" => Ok(Command::Down(adjustment)),
+            _ => Err(format!("Could not parse command: {}", cmd))
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Command::Forward(adjustment) => write!(f, "forward {}", adjustment),
+            Command::Up(adjustment) => write!(f, "up {}", adjustment),
+            Command::Down(adjustment) => write!(f, "down {}", adjustment),
+        }
+    }
+}
+
+// Text form of one extended-dialect command, e.g. `left 90` or `port 3`.
+// Turns are given in degrees and must be whole quarter turns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ExtendedCommand {
+    Forward(i64),
+    Back(i64),
+    Up(i64),
+    Down(i64),
+    Left(i64),
+    Right(i64),
+    Port(i64),
+    Starboard(i64),
+}
+
+impl FromStr for ExtendedCommand {
+    type Err = String;
+    fn from_str(cmd: &str) -> Result<Self, Self::Err> {
+        let mut tokens = cmd.split_whitespace();
+        let action = tokens.next().ok_or(format!("Could not parse command: {}", cmd))?;
+        let adjustment = tokens.next().ok_or(format!("Could not read adjustment: {}", cmd))?;
+        let adjustment = adjustment.parse::<i64>().map_err(|_| format!("Could not parse adjustment: {}", cmd))?;
+
+        let quarter_turns = || {
+            if adjustment % 90 != 0 {
+                return Err(format!("Turns must be a multiple of 90 degrees: {}", cmd));
+            }
+            Ok(adjustment / 90)
+        };
+
+        match action.to_lowercase().as_str() {
+            "forward" => Ok(ExtendedCommand::Forward(adjustment)),
+            "back" => Ok(ExtendedCommand::Back(adjustment)),
+            "up" => Ok(ExtendedCommand::Up(adjustment)),
+            "down" => Ok(ExtendedCommand::Down(adjustment)),
+            "left" => Ok(ExtendedCommand::Left(quarter_turns()?)),
+            "right" => Ok(ExtendedCommand::Right(quarter_turns()?)),
+            "port" => Ok(ExtendedCommand::Port(adjustment)),
+            "starboard" => Ok(ExtendedCommand::Starboard(adjustment)),
+            _ => Err(format!("Could not parse command: {}", cmd))
+        }
+    }
+}
+
+// Long scripts can easily move the submarine past what an i64 holds, so every
+// position update is checked instead of wrapping.
+fn checked_add(position: i64, change: i64) -> Result<i64, String> {
+    position.checked_add(change).ok_or(format!("Overflow adding {} to {}", change, position))
+}
+
+fn checked_neg(value: i64) -> Result<i64, String> {
+    value.checked_neg().ok_or(format!("Overflow negating {}", value))
+}
+
+trait MovementModel {
+    type Command: Copy;
+
+    fn apply(&mut self, command: Self::Command) -> Result<(), String>;
+    fn depth(&self) -> i64;
+    fn horizontal(&self) -> i64;
+
+    fn aim(&self) -> Option<i64> {
+        None
+    }
+
+    fn product(&self) -> Result<i64, String> {
+        self.depth().checked_mul(self.horizontal()).ok_or(format!("Overflow multiplying depth {} by horizontal {}", self.depth(), self.horizontal()))
+    }
+}
 
 struct SubPosition {
-    depth: i32,
-    horizontal: i32,
+    depth: i64,
+    horizontal: i64,
 }
 
 impl SubPosition {
-    fn add_position(&mut self, sub_pos: SubPosition) {
-        self.depth += sub_pos.depth;
-        self.horizontal += sub_pos.horizontal;
+    fn add_position(&mut self, sub_pos: SubPosition) -> Result<(), String> {
+        self.depth = checked_add(self.depth, sub_pos.depth)?;
+        self.horizontal = checked_add(self.horizontal, sub_pos.horizontal)?;
+
+        Ok(())
     }
 
     fn empty() -> SubPosition {
@@ -19,10 +134,32 @@ impl SubPosition {
     }
 }
 
+impl MovementModel for SubPosition {
+    type Command = Command;
+
+    fn apply(&mut self, command: Command) -> Result<(), String> {
+        let update_pos = match command {
+            Command::Forward(adjustment) => SubPosition { depth: 0, horizontal: adjustment },
+            Command::Up(adjustment) => SubPosition { depth: checked_neg(adjustment)?, horizontal: 0 },
+            Command::Down(adjustment) => SubPosition { depth: adjustment, horizontal: 0 },
+        };
+
+        self.add_position(update_pos)
+    }
+
+    fn depth(&self) -> i64 {
+        self.depth
+    }
+
+    fn horizontal(&self) -> i64 {
+        self.horizontal
+    }
+}
+
 struct SubPositionWithAim {
-    depth: i32,
-    horizontal: i32,
-    aim: i32,
+    depth: i64,
+    horizontal: i64,
+    aim: i64,
 }
 
 impl SubPositionWithAim {
@@ -35,79 +172,540 @@ impl SubPositionWithAim {
     }
 }
 
-fn part_one(input: &str) -> i32 {
-    let mut sub_pos = SubPosition::empty();
+impl MovementModel for SubPositionWithAim {
+    type Command = Command;
+
+    fn apply(&mut self, command: Command) -> Result<(), String> {
+        match command {
+            Command::Forward(adjustment) => {
+                let descent = self.aim.checked_mul(adjustment).ok_or(format!("Overflow multiplying aim {} by {}", self.aim, adjustment))?;
+                self.horizontal = checked_add(self.horizontal, adjustment)?;
+                self.depth = checked_add(self.depth, descent)?;
+            },
+            Command::Up(adjustment) => self.aim = checked_add(self.aim, checked_neg(adjustment)?)?,
+            Command::Down(adjustment) => self.aim = checked_add(self.aim, adjustment)?,
+        }
+
+        Ok(())
+    }
+
+    fn depth(&self) -> i64 {
+        self.depth
+    }
+
+    fn horizontal(&self) -> i64 {
+        self.horizontal
+    }
+
+    fn aim(&self) -> Option<i64> {
+        Some(self.aim)
+    }
+}
+
+// Heading is counted in quarter turns anticlockwise from east, so the
+// submarine starts out moving the same way as the classic `forward`.
+struct SubPosition3D {
+    east: i64,
+    north: i64,
+    depth: i64,
+    heading: i64,
+}
+
+impl SubPosition3D {
+    fn empty() -> SubPosition3D {
+        SubPosition3D {
+            east: 0,
+            north: 0,
+            depth: 0,
+            heading: 0,
+        }
+    }
+
+    fn travel(&mut self, heading: i64, distance: i64) -> Result<(), String> {
+        match heading.rem_euclid(4) {
+            0 => self.east = checked_add(self.east, distance)?,
+            1 => self.north = checked_add(self.north, distance)?,
+            2 => self.east = checked_add(self.east, checked_neg(distance)?)?,
+            _ => self.north = checked_add(self.north, checked_neg(distance)?)?,
+        }
+
+        Ok(())
+    }
+}
+
+impl MovementModel for SubPosition3D {
+    type Command = ExtendedCommand;
+
+    fn apply(&mut self, command: ExtendedCommand) -> Result<(), String> {
+        match command {
+            ExtendedCommand::Forward(distance) => self.travel(self.heading, distance)?,
+            ExtendedCommand::Back(distance) => self.travel(self.heading + 2, distance)?,
+            ExtendedCommand::Up(distance) => self.depth = checked_add(self.depth, checked_neg(distance)?)?,
+            ExtendedCommand::Down(distance) => self.depth = checked_add(self.depth, distance)?,
+            ExtendedCommand::Left(turns) => self.heading = (self.heading + turns.rem_euclid(4)).rem_euclid(4),
+            ExtendedCommand::Right(turns) => self.heading = (self.heading - turns.rem_euclid(4)).rem_euclid(4),
+            ExtendedCommand::Port(distance) => self.travel(self.heading + 1, distance)?,
+            ExtendedCommand::Starboard(distance) => self.travel(self.heading - 1, distance)?,
+        }
+
+        Ok(())
+    }
+
+    fn depth(&self) -> i64 {
+        self.depth
+    }
+
+    fn horizontal(&self) -> i64 {
+        self.east
+    }
+}
+
+// A command script is a list of commands, optionally grouped into nested
+// `repeat N { ... }` blocks. Blank lines and `#` comments are ignored.
+#[derive(Debug)]
+enum ScriptItem<C> {
+    Command(C),
+    Repeat(u64, Vec<ScriptItem<C>>),
+}
+
+#[derive(Debug)]
+struct Script<C> {
+    items: Vec<ScriptItem<C>>,
+}
+
+impl<C: FromStr<Err = String>> FromStr for Script<C> {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut items = Vec::<ScriptItem<C>>::new();
+        let mut open_blocks = Vec::<(u64, Vec<ScriptItem<C>>, usize)>::new();
+
+        for (index, line) in s.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if line == "}" {
+                let (count, parent, _) = open_blocks.pop().ok_or(format!("Unmatched '}}' on line {}", line_number))?;
+                let block = mem::replace(&mut items, parent);
+                items.push(ScriptItem::Repeat(count, block));
+                continue;
+            }
+
+            if let Some(repeat) = line.strip_prefix("repeat") {
+                let count = repeat
+                    .strip_suffix('{')
+                    .ok_or(format!("Expected '{{' at the end of line {}: {}", line_number, line))?;
+                let count: u64 = count
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("Could not parse repeat count on line {}: {}", line_number, line))?;
+
+                open_blocks.push((count, mem::take(&mut items), line_number));
+                continue;
+            }
+
+            let command: C = line.parse().map_err(|e| format!("Line {}: {}", line_number, e))?;
+            items.push(ScriptItem::Command(command));
+        }
+
+        if let Some((_, _, line_number)) = open_blocks.last() {
+            return Err(format!("Repeat block opened on line {} is never closed", line_number));
+        }
+
+        Ok(Script { items })
+    }
+}
+
+impl<C: Copy> Script<C> {
+    fn commands(&self) -> ScriptCommands<'_, C> {
+        ScriptCommands {
+            frames: vec![ScriptFrame { items: &self.items, index: 0, remaining: 1 }],
+        }
+    }
+}
+
+struct ScriptFrame<'a, C> {
+    items: &'a [ScriptItem<C>],
+    index: usize,
+    remaining: u64,
+}
+
+// Walks the script one command at a time, so repeat blocks are never
+// expanded in memory.
+struct ScriptCommands<'a, C> {
+    frames: Vec<ScriptFrame<'a, C>>,
+}
+
+impl<C: Copy> Iterator for ScriptCommands<'_, C> {
+    type Item = C;
+
+    fn next(&mut self) -> Option<C> {
+        loop {
+            let frame = self.frames.last_mut()?;
+
+            if frame.index == frame.items.len() {
+                if frame.remaining > 1 {
+                    frame.remaining -= 1;
+                    frame.index = 0;
+                } else {
+                    self.frames.pop();
+                }
+                continue;
+            }
+
+            let item = &frame.items[frame.index];
+            frame.index += 1;
+
+            match item {
+                ScriptItem::Command(command) => return Some(*command),
+                ScriptItem::Repeat(count, items) => {
+                    if *count > 0 && !items.is_empty() {
+                        self.frames.push(ScriptFrame { items, index: 0, remaining: *count });
+                    }
+                },
+            }
+        }
+    }
+}
+
+fn parse_script<C: FromStr<Err = String>>(input: &str) -> Script<C> {
+    input.parse().expect("Error parsing command script")
+}
+
+fn run_commands<M: MovementModel>(mut model: M, commands: impl IntoIterator<Item = M::Command>) -> Result<M, String> {
+    for command in commands {
+        model.apply(command)?;
+    }
+
+    Ok(model)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct TrajectoryPoint {
+    step: usize,
+    horizontal: i64,
+    depth: i64,
+    aim: Option<i64>,
+}
+
+// Every position the submarine passes through, starting with the position
+// before the first command as step 0.
+struct Trajectory {
+    points: Vec<TrajectoryPoint>,
+}
+
+impl Trajectory {
+    fn record<M: MovementModel>(mut model: M, commands: impl IntoIterator<Item = M::Command>) -> Result<Trajectory, String> {
+        let mut points = Vec::<TrajectoryPoint>::new();
+        points.push(Trajectory::snapshot(0, &model));
+
+        for (index, command) in commands.into_iter().enumerate() {
+            model.apply(command).map_err(|error| format!("Step {}: {}", index + 1, error))?;
+            points.push(Trajectory::snapshot(index + 1, &model));
+        }
+
+        Ok(Trajectory { points })
+    }
+
+    fn snapshot<M: MovementModel>(step: usize, model: &M) -> TrajectoryPoint {
+        TrajectoryPoint {
+            step,
+            horizontal: model.horizontal(),
+            depth: model.depth(),
+            aim: model.aim(),
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        let mut csv = String::from("step,horizontal,depth,aim\n");
+
+        for point in &self.points {
+            let aim = match point.aim {
+                Some(aim) => aim.to_string(),
+                None => String::new(),
+            };
+            csv.push_str(&format!("{},{},{},{}\n", point.step, point.horizontal, point.depth, aim));
+        }
+
+        csv
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Crossing {
+    first_step: Option<usize>,
+    max_depth: i64,
+}
+
+// The target counts as reached on the first step that gets to it from
+// whichever side the course started on, so a target above the start is only
+// reached by going up. Step 0 is the position before the first command.
+fn find_crossing<M: MovementModel>(mut model: M, commands: impl IntoIterator<Item = M::Command>, depth: i64) -> Result<Crossing, String> {
+    let start = model.depth();
+    let reached = |current: i64| if start <= depth { current >= depth } else { current <= depth };
+
+    let mut crossing = Crossing {
+        first_step: if reached(start) { Some(0) } else { None },
+        max_depth: start,
+    };
+
+    for (index, command) in commands.into_iter().enumerate() {
+        model.apply(command).map_err(|error| format!("Step {}: {}", index + 1, error))?;
+        crossing.max_depth = crossing.max_depth.max(model.depth());
+        if crossing.first_step.is_none() && reached(model.depth()) {
+            crossing.first_step = Some(index + 1);
+        }
+    }
+
+    Ok(crossing)
+}
+
+// Both courses share one set of axes so the effect of aim is visible at a
+// glance; depth grows downward as it does in the water.
+fn render_svg(courses: &[(&str, &Trajectory)]) -> String {
+    let height = SVG_HEIGHT + SVG_LEGEND_HEIGHT * courses.len() as f64;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+        SVG_WIDTH, height, SVG_WIDTH, height
+    );
+    svg.push_str(&format!("  <rect width=\"{}\" height=\"{}\" fill=\"white\"/>\n", SVG_WIDTH, height));
+
+    let points = || courses.iter().flat_map(|(_, trajectory)| trajectory.points.iter());
+    let min_horizontal = points().map(|point| point.horizontal).min().unwrap_or(0).min(0);
+    let max_horizontal = points().map(|point| point.horizontal).max().unwrap_or(0).max(0);
+    let min_depth = points().map(|point| point.depth).min().unwrap_or(0).min(0);
+    let max_depth = points().map(|point| point.depth).max().unwrap_or(0).max(0);
+
+    let plot_width = SVG_WIDTH - SVG_MARGIN * 2.0;
+    let plot_height = SVG_HEIGHT - SVG_MARGIN * 2.0;
+    let x = |horizontal: i64| SVG_MARGIN + (horizontal - min_horizontal) as f64 * plot_width / (max_horizontal - min_horizontal).max(1) as f64;
+    let y = |depth: i64| SVG_MARGIN + (depth - min_depth) as f64 * plot_height / (max_depth - min_depth).max(1) as f64;
+
+    svg.push_str(&format!(
+        "  <line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke=\"#999999\"/>\n",
+        SVG_MARGIN, y(0), SVG_WIDTH - SVG_MARGIN, y(0)
+    ));
+    svg.push_str(&format!("  <text x=\"5\" y=\"{:.2}\" font-size=\"12\">{}</text>\n", y(min_depth) + 4.0, min_depth));
+    svg.push_str(&format!("  <text x=\"5\" y=\"{:.2}\" font-size=\"12\">{}</text>\n", y(max_depth) + 4.0, max_depth));
+    svg.push_str(&format!("  <text x=\"{:.2}\" y=\"{:.2}\" font-size=\"12\" text-anchor=\"end\">horizontal {}</text>\n", x(max_horizontal), SVG_MARGIN - 10.0, max_horizontal));
+
+    for (index, (name, trajectory)) in courses.iter().enumerate() {
+        let colour = SVG_COLOURS[index % SVG_COLOURS.len()];
+        let coordinates: Vec<String> = trajectory.points.iter().map(|point| format!("{:.2},{:.2}", x(point.horizontal), y(point.depth))).collect();
+        svg.push_str(&format!("  <polyline fill=\"none\" stroke=\"{}\" stroke-width=\"1.5\" points=\"{}\"/>\n", colour, coordinates.join(" ")));
+
+        let last = trajectory.points.last().expect("Trajectories always start with a point");
+        svg.push_str(&format!("  <circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"3\" fill=\"{}\"/>\n", x(last.horizontal), y(last.depth), colour));
+        svg.push_str(&format!(
+            "  <text x=\"{}\" y=\"{:.2}\" font-size=\"12\" fill=\"{}\">{}: horizontal {}, depth {}, product {}</text>\n",
+            SVG_MARGIN, SVG_HEIGHT + SVG_LEGEND_HEIGHT * index as f64, colour, name, last.horizontal, last.depth, last.horizontal as i128 * last.depth as i128
+        ));
+    }
+
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+fn part_one(input: &str) -> i64 {
+    let sub_pos = run_commands(SubPosition::empty(), parse_script(input).commands()).expect("Error running commands");
+
+    sub_pos.product().expect("Error computing product")
+}
+
+fn part_two(input: &str) -> i64 {
+    let sub_pos = run_commands(SubPositionWithAim::empty(), parse_script(input).commands()).expect("Error running commands");
 
-    for line in input.lines() {
-        let update_pos = parse_command(line);
+    sub_pos.product().expect("Error computing product")
+}
 
-        sub_pos.add_position(update_pos);
+fn change_aim(amount: i64) -> Command {
+    if amount < 0 {
+        Command::Up(-amount)
+    } else {
+        Command::Down(amount)
     }
+}
 
-    sub_pos.depth * sub_pos.horizontal
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+
+    a
 }
 
-fn parse_command(cmd: &str) -> SubPosition {
-    let mut depth = 0;
-    let mut horizontal = 0;
+// Finds the shortest list of commands that brings the aim model to the given
+// position. Depth only changes while moving forward, so:
+// - no depth needs at most one `forward`
+// - a depth that divides evenly needs one aim change and one `forward`
+// - otherwise an unaimed `forward` followed by an aimed final leg that divides
+//   the depth always works; the gcd of the two is such a leg and is shorter
+//   than the whole distance because the depth does not divide evenly
+fn plan_route(horizontal: i64, depth: i64) -> Result<Vec<Command>, String> {
+    if horizontal < 0 {
+        return Err(format!("Cannot reach horizontal position {} by moving forward", horizontal));
+    }
 
-    let mut tokens = cmd.split_whitespace();
-    let action = tokens.next().unwrap();
-    let adjustment = tokens.next().expect("Could not read adjustmnet");
-    let adjustment = adjustment.parse::<i32>().unwrap();
+    let mut plan = Vec::<Command>::new();
 
-    match action.to_lowercase().as_str() {
-        "forward" => horizontal = adjustment,
-        "up" => depth = -adjustment,
-        "down" => depth = adjustment,
-        _ => panic!("Could not parse command: {}", cmd)
+    if horizontal == 0 {
+        if depth != 0 {
+            return Err(format!("Cannot reach depth {} without moving forward", depth));
+        }
+    } else if depth == 0 {
+        plan.push(Command::Forward(horizontal));
+    } else if depth % horizontal == 0 {
+        plan.push(change_aim(depth / horizontal));
+        plan.push(Command::Forward(horizontal));
+    } else {
+        let last_leg = gcd(depth.unsigned_abs(), horizontal as u64) as i64;
+        plan.push(Command::Forward(horizontal - last_leg));
+        plan.push(change_aim(depth / last_leg));
+        plan.push(Command::Forward(last_leg));
     }
 
-    SubPosition {
-        depth: depth,
-        horizontal: horizontal,
+    let sub_pos = run_commands(SubPositionWithAim::empty(), plan.iter().copied())?;
+    if sub_pos.horizontal != horizontal || sub_pos.depth != depth {
+        return Err(format!("Planned route ends at ({}, {}) instead of ({}, {})", sub_pos.horizontal, sub_pos.depth, horizontal, depth));
     }
+
+    Ok(plan)
 }
 
-fn parse_command_into_position(cmd: &str, sub_pos: &mut SubPositionWithAim) {
-    let tokens:Vec<&str> = cmd.split_whitespace().collect();
-    let action = tokens[0].trim();
-    let adjustment = tokens[1].parse::<i32>().unwrap();
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Dialect {
+    Classic,
+    Extended,
+}
 
-    match action.to_lowercase().as_str() {
-        "forward" => {sub_pos.horizontal += adjustment; sub_pos.depth += sub_pos.aim * adjustment},
-        "up" => sub_pos.aim -= adjustment,
-        "down" => sub_pos.aim += adjustment,
-        _ => panic!("Could not parse command: {}", cmd)
+struct Options {
+    dialect: Dialect,
+    trajectory: Option<usize>,
+    crossing: Option<i64>,
+    plan: Option<(i64, i64)>,
+    svg: bool,
+}
+
+fn parse_part(value: Option<String>) -> usize {
+    match value.as_deref() {
+        Some("one") => 1,
+        Some("two") => 2,
+        other => panic!("Expected part one or two: {:?}", other),
     }
 }
 
-fn part_two(input: &str) -> i32 {
-    let mut sub_pos = SubPositionWithAim::empty();
+fn parse_options() -> Options {
+    let mut options = Options { dialect: Dialect::Classic, trajectory: None, crossing: None, plan: None, svg: false };
+    let mut args = env::args().skip(1);
 
-    for line in input.lines() {
-        parse_command_into_position(line, &mut sub_pos);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dialect" => {
+                options.dialect = match args.next().as_deref() {
+                    Some("classic") => Dialect::Classic,
+                    Some("extended") => Dialect::Extended,
+                    other => panic!("--dialect must be classic or extended: {:?}", other),
+                }
+            },
+            "--trajectory" => options.trajectory = Some(parse_part(args.next())),
+            "--crossing" => {
+                let depth = args.next().expect("Missing value for --crossing");
+                options.crossing = Some(depth.parse().expect("--crossing must be a depth"));
+            },
+            "--svg" => options.svg = true,
+            "--plan" => {
+                let target = args.next().expect("Missing value for --plan");
+                let mut tokens = target.split(',');
+                let horizontal = tokens.next().unwrap().trim().parse().expect("--plan must be horizontal,depth");
+                let depth = tokens.next().expect("--plan must be horizontal,depth").trim().parse().expect("--plan must be horizontal,depth");
+                options.plan = Some((horizontal, depth));
+            },
+            _ => panic!("Unknown argument: {}", arg),
+        }
     }
 
-    sub_pos.depth * sub_pos.horizontal
+    options
+}
+
+fn record_part(input: &str, part: usize) -> Trajectory {
+    let script = parse_script(input);
+
+    match part {
+        1 => Trajectory::record(SubPosition::empty(), script.commands()),
+        _ => Trajectory::record(SubPositionWithAim::empty(), script.commands()),
+    }.expect("Error recording trajectory")
 }
 
 fn main() {
+    let options = parse_options();
+
+    if let Some((horizontal, depth)) = options.plan {
+        for command in plan_route(horizontal, depth).expect("Error planning route") {
+            println!("{}", command);
+        }
+        return;
+    }
+
     let mut buffer = String::new();
     io::stdin().read_to_string(& mut buffer).expect("Failed to read input");
 
+    if options.dialect == Dialect::Extended {
+        if options.trajectory.is_some() || options.crossing.is_some() || options.svg {
+            panic!("Trajectories are only available for the classic dialect");
+        }
+
+        let sub_pos = run_commands(SubPosition3D::empty(), parse_script(&buffer).commands()).expect("Error running commands");
+        println!("Day 2 extended position: east {}, north {}, depth {}", sub_pos.east, sub_pos.north, sub_pos.depth);
+        return;
+    }
+
+    if options.trajectory.is_some() && options.svg {
+        panic!("--trajectory and --svg cannot be combined");
+    }
+
+    if options.svg {
+        let without_aim = record_part(&buffer, 1);
+        let with_aim = record_part(&buffer, 2);
+        print!("{}", render_svg(&[("part one", &without_aim), ("part two", &with_aim)]));
+        return;
+    }
+
+    if let Some(part) = options.trajectory {
+        print!("{}", record_part(&buffer, part).to_csv());
+        return;
+    }
+
     let part_one_result = part_one(&buffer);
     println!("Day 2 part one: {}", part_one_result);
 
     let part_two_result = part_two(&buffer);
     println!("Day 2 part two: {}", part_two_result);
+
+    if let Some(depth) = options.crossing {
+        let script = parse_script(&buffer);
+        let crossings = [
+            find_crossing(SubPosition::empty(), script.commands(), depth),
+            find_crossing(SubPositionWithAim::empty(), script.commands(), depth),
+        ];
+
+        for (index, crossing) in crossings.into_iter().enumerate() {
+            let crossing = crossing.expect("Error finding crossing");
+            match crossing.first_step {
+                Some(step) => println!("Day 2 part {} first reaches depth {} at step {} (max depth {})", index + 1, depth, step, crossing.max_depth),
+                None => println!("Day 2 part {} never reaches depth {} (max depth {})", index + 1, depth, crossing.max_depth),
+            }
+        }
+    }
 }
 
 #[test]
 fn test_add_position() {
     let mut x = SubPosition {depth: 10, horizontal: 5};
     let y = SubPosition {depth: -3, horizontal: 2};
-    x.add_position(y);
+    x.add_position(y).unwrap();
 
     assert_eq!(7, x.depth);
     assert_eq!(7, x.horizontal);
@@ -115,17 +713,33 @@ fn test_add_position() {
 
 #[test]
 fn test_parse_command() {
-    let sub_pos = parse_command("forward 5");
+    let command: Command = "forward 5".parse().unwrap();
+    assert_eq!(Command::Forward(5), command);
+
+    let command: Command = "up 3".parse().unwrap();
+    assert_eq!(Command::Up(3), command);
+
+    let command: Command = "down 7".parse().unwrap();
+    assert_eq!(Command::Down(7), command);
+
+    assert!("sideways 3".parse::<Command>().is_err());
+    assert!("forward".parse::<Command>().is_err());
+    assert!("forward x".parse::<Command>().is_err());
+}
+
+#[test]
+fn test_sub_position_apply() {
+    let mut sub_pos = SubPosition::empty();
+    sub_pos.apply(Command::Forward(5)).unwrap();
     assert_eq!(0, sub_pos.depth);
     assert_eq!(5, sub_pos.horizontal);
 
-    let sub_pos = parse_command("up 3");
+    sub_pos.apply(Command::Up(3)).unwrap();
     assert_eq!(-3, sub_pos.depth);
-    assert_eq!(0, sub_pos.horizontal);
 
-    let sub_pos = parse_command("down 7");
-    assert_eq!(7, sub_pos.depth);
-    assert_eq!(0, sub_pos.horizontal);
+    sub_pos.apply(Command::Down(7)).unwrap();
+    assert_eq!(4, sub_pos.depth);
+    assert_eq!(20, sub_pos.product().unwrap());
 }
 
 #[test]
@@ -135,12 +749,9 @@ fn test_part_one() {
 }
 
 #[test]
-fn test_parse_command_into_position() {
-    let mut sub_pos = SubPositionWithAim::empty();
-    parse_command_into_position("forward 5\n", &mut sub_pos);
-    parse_command_into_position("down 5\n", &mut sub_pos);
-    parse_command_into_position("forward 8\n", &mut sub_pos);
-    parse_command_into_position("up 3\n", &mut sub_pos);
+fn test_sub_position_with_aim_apply() {
+    let script = parse_script("forward 5\ndown 5\nforward 8\nup 3\n");
+    let sub_pos = run_commands(SubPositionWithAim::empty(), script.commands()).unwrap();
 
     assert_eq!(2, sub_pos.aim);
     assert_eq!(13, sub_pos.horizontal);
@@ -151,4 +762,244 @@ fn test_parse_command_into_position() {
 fn test_part_two() {
     let result = part_two("forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2\n");
     assert_eq!(900, result);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_record_trajectory() {
+    let script = parse_script("forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2\n");
+
+    let trajectory = Trajectory::record(SubPosition::empty(), script.commands()).unwrap();
+    assert_eq!(7, trajectory.points.len());
+    assert_eq!(TrajectoryPoint { step: 0, horizontal: 0, depth: 0, aim: None }, trajectory.points[0]);
+    assert_eq!(TrajectoryPoint { step: 6, horizontal: 15, depth: 10, aim: None }, trajectory.points[6]);
+
+    let trajectory = Trajectory::record(SubPositionWithAim::empty(), script.commands()).unwrap();
+    assert_eq!(TrajectoryPoint { step: 3, horizontal: 13, depth: 40, aim: Some(5) }, trajectory.points[3]);
+}
+
+#[test]
+fn test_find_crossing() {
+    let script = parse_script("forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2\n");
+
+    let crossing = find_crossing(SubPosition::empty(), script.commands(), 5).unwrap();
+    assert_eq!(Crossing { first_step: Some(2), max_depth: 10 }, crossing);
+    let crossing = find_crossing(SubPosition::empty(), script.commands(), 11).unwrap();
+    assert_eq!(Crossing { first_step: None, max_depth: 10 }, crossing);
+
+    let crossing = find_crossing(SubPositionWithAim::empty(), script.commands(), 1).unwrap();
+    assert_eq!(Crossing { first_step: Some(3), max_depth: 60 }, crossing);
+    assert_eq!(Some(6), find_crossing(SubPositionWithAim::empty(), script.commands(), 41).unwrap().first_step);
+}
+
+#[test]
+fn test_find_negative_crossing() {
+    let script = parse_script("forward 5\nup 3\nforward 2\ndown 10\nforward 1\n");
+    let first_step = |depth| find_crossing(SubPosition::empty(), script.commands(), depth).unwrap().first_step;
+
+    assert_eq!(Some(0), first_step(0));
+    assert_eq!(Some(2), first_step(-2));
+    assert_eq!(None, first_step(-4));
+    assert_eq!(Some(4), first_step(5));
+
+    // The aimed course only ever goes deeper, so it never reaches a target above the start.
+    let script = parse_script("down 2\nforward 3\n");
+    assert_eq!(None, find_crossing(SubPositionWithAim::empty(), script.commands(), -1).unwrap().first_step);
+    assert_eq!(Some(2), find_crossing(SubPositionWithAim::empty(), script.commands(), 6).unwrap().first_step);
+}
+
+#[test]
+fn test_trajectory_csv() {
+    let script = parse_script("forward 5\ndown 5\n");
+
+    let trajectory = Trajectory::record(SubPosition::empty(), script.commands()).unwrap();
+    assert_eq!("step,horizontal,depth,aim\n0,0,0,\n1,5,0,\n2,5,5,\n", trajectory.to_csv());
+
+    let trajectory = Trajectory::record(SubPositionWithAim::empty(), script.commands()).unwrap();
+    assert_eq!("step,horizontal,depth,aim\n0,0,0,0\n1,5,0,0\n2,5,0,5\n", trajectory.to_csv());
+}
+
+#[test]
+fn test_parse_extended_command() {
+    let command: ExtendedCommand = "back 4".parse().unwrap();
+    assert_eq!(ExtendedCommand::Back(4), command);
+
+    let command: ExtendedCommand = "left 270".parse().unwrap();
+    assert_eq!(ExtendedCommand::Left(3), command);
+
+    let command: ExtendedCommand = "starboard 2".parse().unwrap();
+    assert_eq!(ExtendedCommand::Starboard(2), command);
+
+    assert!("right 45".parse::<ExtendedCommand>().is_err());
+    assert!("port".parse::<ExtendedCommand>().is_err());
+    assert!("back 3".parse::<Command>().is_err());
+}
+
+#[test]
+fn test_sub_position_3d() {
+    let script = parse_script("forward 10\nleft 90\nforward 4\ndown 3\nstarboard 2\nright 180\nback 1\nport 5\nup 1\n");
+    let sub_pos = run_commands(SubPosition3D::empty(), script.commands()).unwrap();
+
+    assert_eq!(3, sub_pos.heading);
+    assert_eq!(17, sub_pos.east);
+    assert_eq!(5, sub_pos.north);
+    assert_eq!(2, sub_pos.depth);
+}
+
+#[test]
+fn test_extended_dialect_matches_classic_part_one() {
+    let input = "forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2\n";
+    let sub_pos = run_commands(SubPosition3D::empty(), parse_script(input).commands()).unwrap();
+
+    assert_eq!(0, sub_pos.north);
+    assert_eq!(part_one(input), sub_pos.product().unwrap());
+}
+
+#[test]
+fn test_script_comments_and_blank_lines() {
+    let script: Script<Command> = parse_script("# warm up\nforward 5 # ahead\n\n   \ndown 5\n");
+    let commands: Vec<Command> = script.commands().collect();
+    assert_eq!(vec![Command::Forward(5), Command::Down(5)], commands);
+}
+
+#[test]
+fn test_script_repeat_blocks() {
+    let script: Script<Command> = parse_script("repeat 2 {
+        forward 1
+        repeat 3 {
+            down 2
+        }
+    }
+    repeat 0 {
+        up 100
+    }
+    repeat 5 {
+    }
+    up 1");
+
+    let commands: Vec<Command> = script.commands().collect();
+    assert_eq!(vec![
+        Command::Forward(1), Command::Down(2), Command::Down(2), Command::Down(2),
+        Command::Forward(1), Command::Down(2), Command::Down(2), Command::Down(2),
+        Command::Up(1),
+    ], commands);
+}
+
+#[test]
+fn test_script_errors() {
+    assert!("forward 1\n}".parse::<Script<Command>>().unwrap_err().contains("line 2"));
+    assert!("repeat 2 {\nforward 1".parse::<Script<Command>>().unwrap_err().contains("line 1"));
+    assert!("repeat x {\n}".parse::<Script<Command>>().is_err());
+    assert!("repeat 2\nforward 1\n}".parse::<Script<Command>>().is_err());
+    assert!("forward 1\nsideways 2".parse::<Script<Command>>().unwrap_err().starts_with("Line 2"));
+}
+
+#[test]
+fn test_script_with_millions_of_moves() {
+    let input = "repeat 1000 {
+        repeat 1000 {
+            forward 1
+        }
+        down 1
+    }";
+
+    let script: Script<Command> = parse_script(input);
+    assert_eq!(1_001_000, script.commands().count());
+
+    assert_eq!(1_000_000 * 1000, part_one(input));
+    assert_eq!(1_000_000 * 499_500_000, part_two(input));
+}
+
+#[test]
+fn test_script_overflow() {
+    let script: Script<Command> = parse_script("repeat 100000 {
+        down 100000
+        forward 100000
+    }");
+
+    let sub_pos = run_commands(SubPosition::empty(), script.commands()).unwrap();
+    assert_eq!(10_000_000_000, sub_pos.depth());
+    assert!(sub_pos.product().unwrap_err().starts_with("Overflow"));
+
+    assert!(run_commands(SubPositionWithAim::empty(), script.commands()).is_err());
+    assert!(Trajectory::record(SubPositionWithAim::empty(), script.commands()).is_err());
+    assert!(run_commands(SubPosition::empty(), [Command::Up(i64::MIN)]).is_err());
+}
+
+#[test]
+fn test_command_display_round_trip() {
+    for input in ["forward 5", "up 3", "down 7"] {
+        let command: Command = input.parse().unwrap();
+        assert_eq!(input, command.to_string());
+    }
+}
+
+#[test]
+fn test_plan_route() {
+    assert_eq!(Vec::<Command>::new(), plan_route(0, 0).unwrap());
+    assert_eq!(vec![Command::Forward(7)], plan_route(7, 0).unwrap());
+    assert_eq!(vec![Command::Down(4), Command::Forward(15)], plan_route(15, 60).unwrap());
+    assert_eq!(vec![Command::Up(2), Command::Forward(5)], plan_route(5, -10).unwrap());
+    assert_eq!(vec![Command::Forward(4), Command::Down(12), Command::Forward(1)], plan_route(5, 12).unwrap());
+    assert_eq!(vec![Command::Forward(6), Command::Down(3), Command::Forward(1)], plan_route(7, 3).unwrap());
+    assert_eq!(vec![Command::Forward(6), Command::Down(3), Command::Forward(6)], plan_route(12, 18).unwrap());
+    assert_eq!(
+        vec![Command::Forward(999_999_999_999), Command::Down(7), Command::Forward(1)],
+        plan_route(1_000_000_000_000, 7).unwrap()
+    );
+    assert_eq!(
+        vec![Command::Forward(2_147_483_646), Command::Up(1_000_000_007), Command::Forward(1)],
+        plan_route(2_147_483_647, -1_000_000_007).unwrap()
+    );
+    assert_eq!(vec![Command::Forward(6), Command::Up(11), Command::Forward(1)], plan_route(7, -11).unwrap());
+
+    assert!(plan_route(0, 5).is_err());
+    assert!(plan_route(-1, 0).is_err());
+}
+
+#[test]
+fn test_plan_route_replays() {
+    for horizontal in 0..25 {
+        for depth in -60..60 {
+            let plan = match plan_route(horizontal, depth) {
+                Ok(plan) => plan,
+                Err(_) => {
+                    assert!(horizontal == 0 && depth != 0);
+                    continue;
+                }
+            };
+
+            let script: String = plan.iter().map(|command| format!("{}\n", command)).collect();
+            let sub_pos = run_commands(SubPositionWithAim::empty(), parse_script(&script).commands()).unwrap();
+            assert_eq!((horizontal, depth), (sub_pos.horizontal, sub_pos.depth));
+            assert!(plan.len() <= 3);
+            assert!(plan.iter().all(|command| !matches!(command, Command::Forward(0) | Command::Up(0) | Command::Down(0))));
+        }
+    }
+}
+
+#[test]
+fn test_render_svg() {
+    let input = String::from("forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2");
+    let without_aim = record_part(&input, 1);
+    let with_aim = record_part(&input, 2);
+    let svg = render_svg(&[("part one", &without_aim), ("part two", &with_aim)]);
+
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.ends_with("</svg>\n"));
+    assert_eq!(2, svg.matches("<polyline").count());
+    assert!(svg.contains("part one: horizontal 15, depth 10, product 150"));
+    assert!(svg.contains("part two: horizontal 15, depth 60, product 900"));
+
+    // The deepest point of the aimed course sits at the bottom of the plot.
+    let bottom = format!("{:.2},{:.2}", SVG_WIDTH - SVG_MARGIN, SVG_HEIGHT - SVG_MARGIN);
+    assert!(svg.contains(&bottom));
+
+    let empty = record_part("", 1);
+    let svg = render_svg(&[("part one", &empty)]);
+    assert!(svg.contains("part one: horizontal 0, depth 0, product 0"));
+}
@@ -0,0 +1,237 @@
+use std::io::{self, Read};
+use std::str::FromStr;
+
+const ALGORITHM_LENGTH: usize = 512;
+
+fn parse_pixel(c: char) -> Result<bool, String> {
+    match c {
+        '#' => Ok(true),
+        '.' => Ok(false),
+        _ => Err(format!("Could not parse pixel: {}", c)),
+    }
+}
+
+struct Algorithm {
+    lookup: Vec<bool>,
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lookup = Vec::<bool>::new();
+        for c in s.chars().filter(|c| !c.is_whitespace()) {
+            lookup.push(parse_pixel(c)?);
+        }
+
+        if lookup.len() != ALGORITHM_LENGTH {
+            return Err(format!("Enhancement algorithm must have {} entries, found {}", ALGORITHM_LENGTH, lookup.len()));
+        }
+
+        Ok(Algorithm { lookup })
+    }
+}
+
+// Only the bounded region that has ever been touched is stored; every pixel
+// outside of it shares the same background value.
+struct Image {
+    pixels: Vec<Vec<bool>>,
+    background: bool,
+}
+
+impl FromStr for Image {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut pixels = Vec::<Vec<bool>>::new();
+
+        for line in s.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut row = Vec::<bool>::new();
+            for c in line.chars() {
+                row.push(parse_pixel(c)?);
+            }
+
+            if let Some(first) = pixels.first() {
+                if first.len() != row.len() {
+                    return Err(format!("Image rows must all be the same width: {}", line));
+                }
+            }
+
+            pixels.push(row);
+        }
+
+        Ok(Image { pixels, background: false })
+    }
+}
+
+impl Image {
+    fn height(&self) -> i64 {
+        self.pixels.len() as i64
+    }
+
+    fn width(&self) -> i64 {
+        match self.pixels.first() {
+            Some(row) => row.len() as i64,
+            None => 0,
+        }
+    }
+
+    fn get(&self, row: i64, col: i64) -> bool {
+        if row < 0 || col < 0 || row >= self.height() || col >= self.width() {
+            return self.background;
+        }
+
+        self.pixels[row as usize][col as usize]
+    }
+
+    fn enhance(&self, algorithm: &Algorithm) -> Image {
+        let mut pixels = Vec::<Vec<bool>>::new();
+
+        for row in -1..self.height() + 1 {
+            let mut new_row = Vec::<bool>::new();
+            for col in -1..self.width() + 1 {
+                let mut index = 0;
+                for row_offset in -1..2 {
+                    for col_offset in -1..2 {
+                        index <<= 1;
+                        if self.get(row + row_offset, col + col_offset) {
+                            index |= 1;
+                        }
+                    }
+                }
+                new_row.push(algorithm.lookup[index]);
+            }
+            pixels.push(new_row);
+        }
+
+        let background = if self.background {
+            algorithm.lookup[ALGORITHM_LENGTH - 1]
+        } else {
+            algorithm.lookup[0]
+        };
+
+        Image { pixels, background }
+    }
+
+    fn enhance_times(&self, algorithm: &Algorithm, steps: usize) -> Image {
+        let mut image = Image { pixels: self.pixels.clone(), background: self.background };
+
+        for _i in 0..steps {
+            image = image.enhance(algorithm);
+        }
+
+        image
+    }
+
+    // Returns None when the infinite background is lit.
+    fn count_lit(&self) -> Option<usize> {
+        if self.background {
+            return None;
+        }
+
+        Some(self.pixels.iter().map(|row| row.iter().filter(|pixel| **pixel).count()).sum())
+    }
+}
+
+fn parse_input(input: &str) -> (Algorithm, Image) {
+    let input = input.trim_start();
+    let split = input.find("\n\n").or_else(|| input.find("\r\n\r\n")).expect("Missing blank line after algorithm");
+    let (algorithm, image) = input.split_at(split);
+
+    let algorithm: Algorithm = algorithm.parse().expect("Error parsing enhancement algorithm");
+    let image: Image = image.parse().expect("Error parsing image");
+
+    (algorithm, image)
+}
+
+fn count_after_steps(input: &str, steps: usize) -> usize {
+    let (algorithm, image) = parse_input(input);
+    let image = image.enhance_times(&algorithm, steps);
+
+    image.count_lit().expect("Infinitely many pixels are lit")
+}
+
+fn part_one(input: &str) -> usize {
+    count_after_steps(input, 2)
+}
+
+fn part_two(input: &str) -> usize {
+    count_after_steps(input, 50)
+}
+
+fn main() {
+    let mut buffer = String::new();
+    io::stdin().read_to_string(&mut buffer).expect("Error reading input");
+
+    let result = part_one(&buffer);
+    println!("Day 20 part one: {}", result);
+
+    let result = part_two(&buffer);
+    println!("Day 20 part two: {}", result);
+}
+
+#[test]
+fn test_parse_input() {
+    let algorithm = "#".repeat(ALGORITHM_LENGTH);
+    let input = format!("{}\n\n#..#.\n#....\n##..#\n..#..\n..###\n", algorithm);
+    let (algorithm, image) = parse_input(&input);
+
+    assert!(algorithm.lookup.iter().all(|pixel| *pixel));
+    assert_eq!(5, image.height());
+    assert_eq!(5, image.width());
+    assert!(image.get(0, 0));
+    assert!(!image.get(0, 1));
+    assert!(!image.get(-1, -1));
+    assert_eq!(Some(10), image.count_lit());
+
+    assert!("#.#".parse::<Algorithm>().is_err());
+    assert!("#.\n#".parse::<Image>().is_err());
+    assert!("#x".parse::<Image>().is_err());
+}
+
+#[test]
+fn test_flipping_background() {
+    let mut algorithm = ".".repeat(ALGORITHM_LENGTH);
+    algorithm.replace_range(0..1, "#");
+    let algorithm: Algorithm = algorithm.parse().unwrap();
+    let image: Image = String::from("...\n...\n...").parse().unwrap();
+
+    let image = image.enhance(&algorithm);
+    assert!(image.background);
+    assert_eq!(None, image.count_lit());
+    assert_eq!(5, image.height());
+
+    let image = image.enhance(&algorithm);
+    assert!(!image.background);
+    assert_eq!(Some(0), image.count_lit());
+    assert_eq!(7, image.width());
+}
+
+#[test]
+fn test_part_one() {
+    let input = String::from("..#.#..#####.#.#.#.###.##.....###.##.#..###.####..#####..#....#..#..##..###..######.###...####..#..#####..##..#.#####...##.#.#..#.##..#.#......#.###.######.###.####...#.##.##..#..#..#####.....#.#....###..#.##......#.....#..#..#..##..#...##.######.####.####.#.#...#.......#..#.#.#...####.##.#......#..#...##.#.##..#...##.#.##..###.#......#.#.......#.#.#.####.###.##...#.....####.#..#..#.##.#....##..#.####....##...##..#...#......#.#.......#.......##..####..#...#.#.#...##..#.#..###..#####........#..####......#..#
+
+    #..#.
+    #....
+    ##..#
+    ..#..
+    ..###");
+
+    assert_eq!(35, part_one(&input));
+}
+
+#[test]
+fn test_part_two() {
+    let input = String::from("..#.#..#####.#.#.#.###.##.....###.##.#..###.####..#####..#....#..#..##..###..######.###...####..#..#####..##..#.#####...##.#.#..#.##..#.#......#.###.######.###.####...#.##.##..#..#..#####.....#.#....###..#.##......#.....#..#..#..##..#...##.######.####.####.#.#...#.......#..#.#.#...####.##.#......#..#...##.#.##..#...##.#.##..###.#......#.#.......#.#.#.####.###.##...#.....####.#..#..#.##.#....##..#.####....##...##..#...#......#.#.......#.......##..####..#...#.#.#...##..#.#..###..#####........#..####......#..#
+
+    #..#.
+    #....
+    ##..#
+    ..#..
+    ..###");
+
+    assert_eq!(3351, part_two(&input));
+}
@@ -0,0 +1,264 @@
+use std::io::{self, Read};
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug)]
+struct Rules {
+    board_size: u64,
+    die_sides: u64,
+    rolls_per_turn: u64,
+    winning_score: u64,
+}
+
+impl Rules {
+    fn deterministic() -> Rules {
+        Rules { board_size: 10, die_sides: 100, rolls_per_turn: 3, winning_score: 1000 }
+    }
+
+    fn dirac() -> Rules {
+        Rules { board_size: 10, die_sides: 3, rolls_per_turn: 3, winning_score: 21 }
+    }
+
+    // Every distinct total of one turn's rolls, with the number of universes
+    // that produce it.
+    fn roll_outcomes(&self) -> Vec<(u64, u64)> {
+        let mut totals = HashMap::<u64, u64>::new();
+        totals.insert(0, 1);
+
+        for _i in 0..self.rolls_per_turn {
+            let mut next_totals = HashMap::<u64, u64>::new();
+            for (total, universes) in &totals {
+                for face in 1..self.die_sides + 1 {
+                    let count = next_totals.entry(total + face).or_insert(0);
+                    *count += universes;
+                }
+            }
+            totals = next_totals;
+        }
+
+        let mut outcomes: Vec<(u64, u64)> = totals.into_iter().collect();
+        outcomes.sort();
+
+        outcomes
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct GameState {
+    positions: [u64; 2],
+    scores: [u64; 2],
+    turn: usize,
+}
+
+impl GameState {
+    fn new(player_one: u64, player_two: u64) -> GameState {
+        GameState { positions: [player_one, player_two], scores: [0, 0], turn: 0 }
+    }
+
+    fn advance(&self, rules: &Rules, total: u64) -> GameState {
+        let mut next = *self;
+        let player = self.turn;
+
+        next.positions[player] = (self.positions[player] - 1 + total) % rules.board_size + 1;
+        next.scores[player] += next.positions[player];
+        next.turn = 1 - player;
+
+        next
+    }
+
+    fn winner(&self, rules: &Rules) -> Option<usize> {
+        self.scores.iter().position(|score| *score >= rules.winning_score)
+    }
+}
+
+struct DeterministicDie {
+    sides: u64,
+    next: u64,
+    rolls: u64,
+}
+
+impl DeterministicDie {
+    fn new(sides: u64) -> DeterministicDie {
+        DeterministicDie { sides, next: 1, rolls: 0 }
+    }
+
+    fn roll(&mut self) -> u64 {
+        let value = self.next;
+        self.next = self.next % self.sides + 1;
+        self.rolls += 1;
+
+        value
+    }
+}
+
+fn play_deterministic(rules: &Rules, start: GameState) -> u64 {
+    let mut die = DeterministicDie::new(rules.die_sides);
+    let mut state = start;
+
+    while state.winner(rules).is_none() {
+        let mut total = 0;
+        for _i in 0..rules.rolls_per_turn {
+            total += die.roll();
+        }
+        state = state.advance(rules, total);
+    }
+
+    let losing_score = state.scores.iter().min().unwrap();
+
+    losing_score * die.rolls
+}
+
+fn count_wins(rules: &Rules, outcomes: &[(u64, u64)], state: GameState, memo: &mut HashMap<GameState, [u64; 2]>) -> [u64; 2] {
+    if let Some(winner) = state.winner(rules) {
+        let mut wins = [0, 0];
+        wins[winner] = 1;
+        return wins;
+    }
+
+    if let Some(wins) = memo.get(&state) {
+        return *wins;
+    }
+
+    let mut wins = [0, 0];
+    for (total, universes) in outcomes {
+        let result = count_wins(rules, outcomes, state.advance(rules, *total), memo);
+        wins[0] += result[0] * universes;
+        wins[1] += result[1] * universes;
+    }
+
+    memo.insert(state, wins);
+
+    wins
+}
+
+fn play_quantum(rules: &Rules, start: GameState) -> [u64; 2] {
+    let outcomes = rules.roll_outcomes();
+    let mut memo = HashMap::<GameState, [u64; 2]>::new();
+
+    count_wins(rules, &outcomes, start, &mut memo)
+}
+
+fn parse_start(input: &str, rules: &Rules) -> Result<GameState, String> {
+    let mut positions = Vec::<u64>::new();
+
+    for line in input.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let position = line.split(':').nth(1).ok_or(format!("Missing starting position: {}", line))?;
+        let position: u64 = position.trim().parse().map_err(|_| format!("Could not parse starting position: {}", line))?;
+        if position < 1 || position > rules.board_size {
+            return Err(format!("Starting position must be between 1 and {}: {}", rules.board_size, line));
+        }
+        positions.push(position);
+    }
+
+    if positions.len() != 2 {
+        return Err(format!("Expected two players, found {}", positions.len()));
+    }
+
+    Ok(GameState::new(positions[0], positions[1]))
+}
+
+fn part_one(input: &str) -> u64 {
+    let rules = Rules::deterministic();
+    let start = parse_start(input, &rules).expect("Error parsing starting positions");
+
+    play_deterministic(&rules, start)
+}
+
+fn part_two(input: &str) -> u64 {
+    let rules = Rules::dirac();
+    let start = parse_start(input, &rules).expect("Error parsing starting positions");
+    let wins = play_quantum(&rules, start);
+
+    *wins.iter().max().unwrap()
+}
+
+fn main() {
+    let mut buffer = String::new();
+    io::stdin().read_to_string(&mut buffer).expect("Error reading input");
+
+    let result = part_one(&buffer);
+    println!("Day 21 part one: {}", result);
+
+    let result = part_two(&buffer);
+    println!("Day 21 part two: {}", result);
+}
+
+#[test]
+fn test_roll_outcomes() {
+    let outcomes = Rules::dirac().roll_outcomes();
+    assert_eq!(vec![(3, 1), (4, 3), (5, 6), (6, 7), (7, 6), (8, 3), (9, 1)], outcomes);
+
+    let rules = Rules { board_size: 10, die_sides: 6, rolls_per_turn: 2, winning_score: 21 };
+    let outcomes = rules.roll_outcomes();
+    assert_eq!(11, outcomes.len());
+    assert_eq!(36, outcomes.iter().map(|(_, universes)| universes).sum::<u64>());
+}
+
+#[test]
+fn test_advance_wraps_board() {
+    let rules = Rules::deterministic();
+    let state = GameState::new(4, 8);
+
+    let state = state.advance(&rules, 1 + 2 + 3);
+    assert_eq!([10, 8], state.positions);
+    assert_eq!([10, 0], state.scores);
+    assert_eq!(1, state.turn);
+
+    let state = state.advance(&rules, 4 + 5 + 6);
+    assert_eq!([10, 3], state.positions);
+    assert_eq!([10, 3], state.scores);
+    assert_eq!(0, state.turn);
+}
+
+#[test]
+fn test_deterministic_die() {
+    let mut die = DeterministicDie::new(3);
+    let rolls: Vec<u64> = (0..5).map(|_| die.roll()).collect();
+    assert_eq!(vec![1, 2, 3, 1, 2], rolls);
+    assert_eq!(5, die.rolls);
+}
+
+#[test]
+fn test_parse_start() {
+    let rules = Rules::dirac();
+    assert_eq!(Ok(GameState::new(1, 10)), parse_start("Player 1 starting position: 1\nPlayer 2 starting position: 10\n", &rules));
+
+    assert!(parse_start("Player 1 starting position: 0\nPlayer 2 starting position: 8\n", &rules).is_err());
+    assert!(parse_start("Player 1 starting position: 4\nPlayer 2 starting position: 11\n", &rules).is_err());
+    assert!(parse_start("Player 1 starting position: 4\n", &rules).is_err());
+    assert!(parse_start("Player 1 starting position: x\nPlayer 2 starting position: 8\n", &rules).is_err());
+
+    let small = Rules { board_size: 4, die_sides: 2, rolls_per_turn: 1, winning_score: 3 };
+    assert!(parse_start("Player 1 starting position: 4\nPlayer 2 starting position: 5\n", &small).is_err());
+}
+
+#[test]
+fn test_custom_board() {
+    let rules = Rules { board_size: 4, die_sides: 2, rolls_per_turn: 1, winning_score: 3 };
+    let wins = play_quantum(&rules, GameState::new(1, 1));
+
+    // Player one wins outright by rolling a 2. Otherwise both players sit on
+    // 2 points and player two wins on a 2, or player one wins on either roll.
+    assert_eq!([3, 1], wins);
+}
+
+#[test]
+fn test_part_one() {
+    let input = String::from("Player 1 starting position: 4
+    Player 2 starting position: 8");
+    assert_eq!(739785, part_one(&input));
+}
+
+#[test]
+fn test_part_two() {
+    let input = String::from("Player 1 starting position: 4
+    Player 2 starting position: 8");
+    assert_eq!(444356092776315, part_two(&input));
+
+    let wins = play_quantum(&Rules::dirac(), parse_start(&input, &Rules::dirac()).unwrap());
+    assert_eq!(341960390180808, wins[1]);
+}
@@ -0,0 +1,428 @@
+use std::io::{self, Read};
+use std::str::FromStr;
+use std::collections::{BinaryHeap, HashMap};
+use std::cmp::{self, Reverse};
+use std::env;
+use std::fmt;
+
+const HALLWAY_LENGTH: usize = 11;
+const ROOMS: usize = 4;
+const BITS_PER_CELL: usize = 3;
+const ENTRANCES: [usize; ROOMS] = [2, 4, 6, 8];
+const UNFOLDED_ROWS: [[u8; ROOMS]; 2] = [[4, 3, 2, 1], [4, 2, 1, 3]];
+
+fn amphipod_char(amphipod: u8) -> char {
+    match amphipod {
+        0 => '.',
+        _ => (b'A' + amphipod - 1) as char,
+    }
+}
+
+fn parse_cell(c: char) -> Result<u8, String> {
+    match c {
+        '.' => Ok(0),
+        'A'..='D' => Ok(c as u8 - b'A' + 1),
+        _ => Err(format!("Could not parse burrow cell: {}", c)),
+    }
+}
+
+fn energy_per_step(amphipod: u8) -> u64 {
+    10u64.pow(amphipod as u32 - 1)
+}
+
+// Cells 0..11 are the hallway, followed by each room from top to bottom.
+// Every cell is packed into three bits of a u128: 0 for empty, 1..=4 for A..=D.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Burrow {
+    depth: usize,
+    cells: u128,
+}
+
+impl FromStr for Burrow {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().map(|line| line.trim()).filter(|line| !line.is_empty());
+
+        lines.next().ok_or("Missing burrow wall")?;
+        let hallway = lines.next().ok_or("Missing hallway")?.trim_matches('#');
+        if hallway.chars().count() != HALLWAY_LENGTH {
+            return Err(format!("Hallway must be {} cells long: {}", HALLWAY_LENGTH, hallway));
+        }
+
+        let mut rows = Vec::<Vec<u8>>::new();
+        for line in lines {
+            let row = line.chars().filter(|c| *c != '#').map(parse_cell).collect::<Result<Vec<u8>, String>>()?;
+            if row.is_empty() {
+                continue;
+            }
+            if row.len() != ROOMS {
+                return Err(format!("Room row must have {} cells: {}", ROOMS, line));
+            }
+            rows.push(row);
+        }
+
+        let mut burrow = Burrow { depth: rows.len(), cells: 0 };
+        if burrow.cell_count() * BITS_PER_CELL > 128 {
+            return Err(format!("Rooms {} deep do not fit into a packed burrow", burrow.depth));
+        }
+
+        for (index, c) in hallway.chars().enumerate() {
+            burrow.set(index, parse_cell(c)?);
+        }
+
+        for (slot, row) in rows.iter().enumerate() {
+            for (room, amphipod) in row.iter().enumerate() {
+                let cell = burrow.room_cell(room, slot);
+                burrow.set(cell, *amphipod);
+            }
+        }
+
+        Ok(burrow)
+    }
+}
+
+impl fmt::Display for Burrow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", "#".repeat(HALLWAY_LENGTH + 2))?;
+
+        let hallway: String = (0..HALLWAY_LENGTH).map(|cell| amphipod_char(self.get(cell))).collect();
+        writeln!(f, "#{}#", hallway)?;
+
+        for slot in 0..self.depth {
+            let row: Vec<String> = (0..ROOMS).map(|room| amphipod_char(self.get(self.room_cell(room, slot))).to_string()).collect();
+            if slot == 0 {
+                writeln!(f, "###{}###", row.join("#"))?;
+            } else {
+                writeln!(f, "  #{}#", row.join("#"))?;
+            }
+        }
+
+        write!(f, "  {}", "#".repeat(ROOMS * 2 + 1))
+    }
+}
+
+impl Burrow {
+    fn cell_count(&self) -> usize {
+        HALLWAY_LENGTH + ROOMS * self.depth
+    }
+
+    fn room_cell(&self, room: usize, slot: usize) -> usize {
+        HALLWAY_LENGTH + room * self.depth + slot
+    }
+
+    fn get(&self, cell: usize) -> u8 {
+        ((self.cells >> (cell * BITS_PER_CELL)) & 0b111) as u8
+    }
+
+    fn set(&mut self, cell: usize, amphipod: u8) {
+        let shift = cell * BITS_PER_CELL;
+        self.cells = (self.cells & !(0b111 << shift)) | ((amphipod as u128) << shift);
+    }
+
+    fn unfold(&self) -> Burrow {
+        let mut unfolded = Burrow { depth: self.depth + UNFOLDED_ROWS.len(), cells: 0 };
+
+        for cell in 0..HALLWAY_LENGTH {
+            unfolded.set(cell, self.get(cell));
+        }
+
+        for room in 0..ROOMS {
+            let mut column = Vec::<u8>::new();
+            for slot in 0..self.depth {
+                column.push(self.get(self.room_cell(room, slot)));
+            }
+
+            let bottom = column.split_off(cmp::min(1, column.len()));
+            for row in UNFOLDED_ROWS {
+                column.push(row[room]);
+            }
+            column.extend(bottom);
+
+            for (slot, amphipod) in column.iter().enumerate() {
+                let cell = unfolded.room_cell(room, slot);
+                unfolded.set(cell, *amphipod);
+            }
+        }
+
+        unfolded
+    }
+
+    fn organised(&self) -> Burrow {
+        let mut organised = Burrow { depth: self.depth, cells: 0 };
+        for room in 0..ROOMS {
+            for slot in 0..self.depth {
+                let cell = organised.room_cell(room, slot);
+                organised.set(cell, room as u8 + 1);
+            }
+        }
+
+        organised
+    }
+
+    // A room only accepts amphipods once it holds nothing but its own kind.
+    fn room_is_settled(&self, room: usize) -> bool {
+        (0..self.depth).all(|slot| {
+            let amphipod = self.get(self.room_cell(room, slot));
+            amphipod == 0 || amphipod as usize == room + 1
+        })
+    }
+
+    fn hallway_clear(&self, from: usize, to: usize) -> bool {
+        let (start, end) = if from < to { (from + 1, to) } else { (to, from - 1) };
+        (start..end + 1).all(|cell| self.get(cell) == 0)
+    }
+
+    fn moves(&self) -> Vec<Move> {
+        let mut moves = Vec::<Move>::new();
+
+        // Hallway amphipods can only go straight home.
+        for position in 0..HALLWAY_LENGTH {
+            let amphipod = self.get(position);
+            if amphipod == 0 {
+                continue;
+            }
+
+            let room = amphipod as usize - 1;
+            let entrance = ENTRANCES[room];
+            if !self.room_is_settled(room) || !self.hallway_clear(position, entrance) {
+                continue;
+            }
+
+            let slot = (0..self.depth).rev().find(|slot| self.get(self.room_cell(room, *slot)) == 0);
+            if let Some(slot) = slot {
+                let steps = position.abs_diff(entrance) + slot + 1;
+                moves.push(Move { amphipod, from: position, to: self.room_cell(room, slot), energy: steps as u64 * energy_per_step(amphipod) });
+            }
+        }
+
+        // The top amphipod of an unsettled room can step out to any hallway
+        // cell that is not directly outside a room.
+        for (room, &entrance) in ENTRANCES.iter().enumerate() {
+            if self.room_is_settled(room) {
+                continue;
+            }
+
+            let slot = (0..self.depth).find(|slot| self.get(self.room_cell(room, *slot)) != 0);
+            let slot = match slot {
+                Some(slot) => slot,
+                None => continue,
+            };
+
+            let from = self.room_cell(room, slot);
+            let amphipod = self.get(from);
+
+            for position in 0..HALLWAY_LENGTH {
+                if ENTRANCES.contains(&position) || self.get(position) != 0 || !self.hallway_clear(entrance, position) {
+                    continue;
+                }
+
+                let steps = slot + 1 + position.abs_diff(entrance);
+                moves.push(Move { amphipod, from, to: position, energy: steps as u64 * energy_per_step(amphipod) });
+            }
+        }
+
+        moves
+    }
+
+    fn apply(&self, movement: &Move) -> Burrow {
+        let mut next = *self;
+        next.set(movement.from, 0);
+        next.set(movement.to, movement.amphipod);
+
+        next
+    }
+
+    fn describe_cell(&self, cell: usize) -> String {
+        if cell < HALLWAY_LENGTH {
+            return format!("hallway {}", cell);
+        }
+
+        let room = (cell - HALLWAY_LENGTH) / self.depth;
+        let slot = (cell - HALLWAY_LENGTH) % self.depth;
+        format!("room {} slot {}", amphipod_char(room as u8 + 1), slot + 1)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Move {
+    amphipod: u8,
+    from: usize,
+    to: usize,
+    energy: u64,
+}
+
+struct Solution {
+    energy: u64,
+    moves: Vec<(Move, Burrow)>,
+}
+
+// Dijkstra over packed burrow states, remembering how each state was first
+// reached so the winning sequence of moves can be replayed.
+fn organise(start: Burrow) -> Option<Solution> {
+    let goal = start.organised();
+    let mut best = HashMap::<u128, u64>::new();
+    let mut previous = HashMap::<u128, (Burrow, Move)>::new();
+    let mut queue = BinaryHeap::<Reverse<(u64, u128)>>::new();
+
+    best.insert(start.cells, 0);
+    queue.push(Reverse((0, start.cells)));
+
+    while let Some(Reverse((energy, cells))) = queue.pop() {
+        if cells == goal.cells {
+            let mut moves = Vec::<(Move, Burrow)>::new();
+            let mut current = goal;
+            while let Some((burrow, movement)) = previous.get(&current.cells) {
+                moves.push((*movement, current));
+                current = *burrow;
+            }
+            moves.reverse();
+
+            return Some(Solution { energy, moves });
+        }
+
+        if energy > best[&cells] {
+            continue;
+        }
+
+        let burrow = Burrow { depth: start.depth, cells };
+        for movement in burrow.moves() {
+            let next = burrow.apply(&movement);
+            let next_energy = energy + movement.energy;
+
+            if best.get(&next.cells).is_none_or(|known| next_energy < *known) {
+                best.insert(next.cells, next_energy);
+                previous.insert(next.cells, (burrow, movement));
+                queue.push(Reverse((next_energy, next.cells)));
+            }
+        }
+    }
+
+    None
+}
+
+fn print_solution(start: &Burrow, solution: &Solution) {
+    println!("{}", start);
+    for (movement, burrow) in &solution.moves {
+        println!();
+        println!("{} moves from {} to {} ({} energy)", amphipod_char(movement.amphipod), start.describe_cell(movement.from), start.describe_cell(movement.to), movement.energy);
+        println!("{}", burrow);
+    }
+    println!();
+}
+
+// Both parts hand back the whole solution so `--moves` can print the same
+// moves that the reported energy comes from.
+fn part_one(burrow: &Burrow) -> Solution {
+    organise(*burrow).expect("Burrow cannot be organised")
+}
+
+fn part_two(burrow: &Burrow) -> Solution {
+    organise(burrow.unfold()).expect("Burrow cannot be organised")
+}
+
+fn main() {
+    let mut buffer = String::new();
+    io::stdin().read_to_string(&mut buffer).expect("Error reading input");
+
+    let show_moves = env::args().any(|arg| arg == "--moves");
+    let burrow: Burrow = buffer.parse().expect("Error parsing burrow");
+
+    let folded = part_one(&burrow);
+    let unfolded = part_two(&burrow);
+
+    if show_moves {
+        print_solution(&burrow, &folded);
+        print_solution(&burrow.unfold(), &unfolded);
+    }
+
+    println!("Day 23 part one: {}", folded.energy);
+    println!("Day 23 part two: {}", unfolded.energy);
+}
+
+#[test]
+fn test_parse_burrow() {
+    let input = String::from("#############
+    #...........#
+    ###B#C#B#D###
+      #A#D#C#A#
+      #########");
+
+    let burrow: Burrow = input.parse().unwrap();
+    assert_eq!(2, burrow.depth);
+    assert_eq!(2, burrow.get(burrow.room_cell(0, 0)));
+    assert_eq!(1, burrow.get(burrow.room_cell(0, 1)));
+    assert_eq!(1, burrow.get(burrow.room_cell(3, 1)));
+    assert_eq!(0, burrow.get(5));
+
+    let rendered = burrow.to_string();
+    assert_eq!("#############\n#...........#\n###B#C#B#D###\n  #A#D#C#A#\n  #########", rendered);
+    assert_eq!(burrow, rendered.parse().unwrap());
+
+    assert!("#############\n#...........#\n###B#C#B#E###\n  #########".parse::<Burrow>().is_err());
+}
+
+#[test]
+fn test_unfold() {
+    let burrow: Burrow = String::from("#############\n#...........#\n###B#C#B#D###\n  #A#D#C#A#\n  #########").parse().unwrap();
+    let unfolded = burrow.unfold();
+
+    assert_eq!(4, unfolded.depth);
+    assert_eq!("#############\n#...........#\n###B#C#B#D###\n  #D#C#B#A#\n  #D#B#A#C#\n  #A#D#C#A#\n  #########", unfolded.to_string());
+}
+
+#[test]
+fn test_moves() {
+    let burrow: Burrow = String::from("#############\n#...........#\n###B#C#B#D###\n  #A#D#C#A#\n  #########").parse().unwrap();
+
+    // Four rooms can each send their top amphipod to any of seven stops.
+    assert_eq!(28, burrow.moves().len());
+
+    let burrow: Burrow = String::from("#############\n#.....D.....#\n###B#.#C#D###\n  #A#B#C#A#\n  #########").parse().unwrap();
+    // The D in the hallway blocks everything beyond it and cannot go home
+    // while its room still holds an A.
+    assert_eq!(6, burrow.moves().len());
+
+    let settled: Burrow = String::from("#############\n#...B.......#\n###B#.#C#D###\n  #A#B#C#A#\n  #########").parse().unwrap();
+    let into_room = settled.moves().into_iter().find(|movement| movement.from == 3).unwrap();
+    assert_eq!(settled.room_cell(1, 0), into_room.to);
+    assert_eq!(20, into_room.energy);
+}
+
+#[test]
+fn test_solution_replays() {
+    let start: Burrow = String::from("#############\n#...........#\n###B#C#B#D###\n  #A#D#C#A#\n  #########").parse().unwrap();
+    let solution = organise(start).unwrap();
+
+    let mut burrow = start;
+    let mut energy = 0;
+    for (movement, expected) in &solution.moves {
+        burrow = burrow.apply(movement);
+        energy += movement.energy;
+        assert_eq!(*expected, burrow);
+    }
+
+    assert_eq!(start.organised(), burrow);
+    assert_eq!(solution.energy, energy);
+}
+
+#[test]
+fn test_part_one() {
+    let input = String::from("#############
+    #...........#
+    ###B#C#B#D###
+      #A#D#C#A#
+      #########");
+    let burrow: Burrow = input.parse().unwrap();
+    assert_eq!(12521, part_one(&burrow).energy);
+}
+
+#[test]
+fn test_part_two() {
+    let input = String::from("#############
+    #...........#
+    ###B#C#B#D###
+      #A#D#C#A#
+      #########");
+    let burrow: Burrow = input.parse().unwrap();
+    assert_eq!(44169, part_two(&burrow).energy);
+}
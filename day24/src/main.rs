@@ -0,0 +1,442 @@
+use std::io::{self, Read};
+use std::str::FromStr;
+
+const MODEL_NUMBER_DIGITS: usize = 14;
+const BLOCK_LENGTH: usize = 18;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Register {
+    W,
+    X,
+    Y,
+    Z,
+}
+
+impl FromStr for Register {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "w" => Ok(Register::W),
+            "x" => Ok(Register::X),
+            "y" => Ok(Register::Y),
+            "z" => Ok(Register::Z),
+            _ => Err(format!("Could not parse register: {}", s)),
+        }
+    }
+}
+
+impl Register {
+    fn index(&self) -> usize {
+        match self {
+            Register::W => 0,
+            Register::X => 1,
+            Register::Y => 2,
+            Register::Z => 3,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operand {
+    Register(Register),
+    Value(i64),
+}
+
+impl FromStr for Operand {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(register) = s.parse::<Register>() {
+            return Ok(Operand::Register(register));
+        }
+
+        match s.parse::<i64>() {
+            Ok(value) => Ok(Operand::Value(value)),
+            Err(_) => Err(format!("Could not parse operand: {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Instruction {
+    Inp(Register),
+    Add(Register, Operand),
+    Mul(Register, Operand),
+    Div(Register, Operand),
+    Mod(Register, Operand),
+    Eql(Register, Operand),
+}
+
+impl FromStr for Instruction {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = s.split_whitespace();
+        let action = tokens.next().ok_or("Empty instruction")?;
+        let register: Register = tokens.next().ok_or(format!("Missing register: {}", s))?.parse()?;
+
+        if action == "inp" {
+            if tokens.next().is_some() {
+                return Err(format!("Unexpected operand: {}", s));
+            }
+            return Ok(Instruction::Inp(register));
+        }
+
+        let operand: Operand = tokens.next().ok_or(format!("Missing operand: {}", s))?.parse()?;
+        if tokens.next().is_some() {
+            return Err(format!("Too many operands: {}", s));
+        }
+
+        match action {
+            "add" => Ok(Instruction::Add(register, operand)),
+            "mul" => Ok(Instruction::Mul(register, operand)),
+            "div" => Ok(Instruction::Div(register, operand)),
+            "mod" => Ok(Instruction::Mod(register, operand)),
+            "eql" => Ok(Instruction::Eql(register, operand)),
+            _ => Err(format!("Could not parse instruction: {}", s)),
+        }
+    }
+}
+
+struct Alu {
+    registers: [i64; 4],
+}
+
+impl Alu {
+    fn new() -> Alu {
+        Alu { registers: [0; 4] }
+    }
+
+    fn get(&self, register: Register) -> i64 {
+        self.registers[register.index()]
+    }
+
+    fn value(&self, operand: Operand) -> i64 {
+        match operand {
+            Operand::Register(register) => self.get(register),
+            Operand::Value(value) => value,
+        }
+    }
+
+    fn execute(&mut self, instruction: &Instruction, inputs: &mut impl Iterator<Item = i64>) -> Result<(), String> {
+        let (register, result) = match *instruction {
+            Instruction::Inp(register) => (register, inputs.next().ok_or("Ran out of input")?),
+            Instruction::Add(register, operand) => {
+                let sum = self.get(register).checked_add(self.value(operand));
+                (register, sum.ok_or(format!("Overflow adding {} + {}: {:?}", self.get(register), self.value(operand), instruction))?)
+            },
+            Instruction::Mul(register, operand) => {
+                let product = self.get(register).checked_mul(self.value(operand));
+                (register, product.ok_or(format!("Overflow multiplying {} * {}: {:?}", self.get(register), self.value(operand), instruction))?)
+            },
+            Instruction::Div(register, operand) => {
+                let divisor = self.value(operand);
+                if divisor == 0 {
+                    return Err(format!("Division by zero: {:?}", instruction));
+                }
+                let quotient = self.get(register).checked_div(divisor);
+                (register, quotient.ok_or(format!("Overflow dividing {} / {}: {:?}", self.get(register), divisor, instruction))?)
+            },
+            Instruction::Mod(register, operand) => {
+                let dividend = self.get(register);
+                let divisor = self.value(operand);
+                if dividend < 0 || divisor <= 0 {
+                    return Err(format!("Invalid modulo {} % {}: {:?}", dividend, divisor, instruction));
+                }
+                (register, dividend % divisor)
+            },
+            Instruction::Eql(register, operand) => (register, (self.get(register) == self.value(operand)) as i64),
+        };
+
+        self.registers[register.index()] = result;
+
+        Ok(())
+    }
+
+    fn run(&mut self, program: &[Instruction], inputs: &[i64]) -> Result<(), String> {
+        let mut inputs = inputs.iter().copied();
+        for instruction in program {
+            self.execute(instruction, &mut inputs)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_program(input: &str) -> Result<Vec<Instruction>, String> {
+    input
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.parse())
+        .collect()
+}
+
+fn is_accepted(program: &[Instruction], digits: &[i64]) -> Result<bool, String> {
+    let mut alu = Alu::new();
+    alu.run(program, digits)?;
+
+    Ok(alu.get(Register::Z) == 0)
+}
+
+// MONAD repeats the same eighteen instruction block for every digit, varying
+// only three constants. Blocks that divide z by 1 push `digit + add_y` onto a
+// base 26 stack in z; blocks that divide by 26 pop it again, which only
+// happens when `digit == popped + add_x`.
+struct Block {
+    divides_z: bool,
+    add_x: i64,
+    add_y: i64,
+}
+
+fn constant(instruction: &Instruction) -> Option<i64> {
+    match instruction {
+        Instruction::Div(Register::Z, Operand::Value(value)) => Some(*value),
+        Instruction::Add(Register::X, Operand::Value(value)) => Some(*value),
+        Instruction::Add(Register::Y, Operand::Value(value)) => Some(*value),
+        _ => None,
+    }
+}
+
+fn parse_blocks(program: &[Instruction]) -> Result<Vec<Block>, String> {
+    if program.len() != MODEL_NUMBER_DIGITS * BLOCK_LENGTH {
+        return Err(format!("Expected {} instructions, found {}", MODEL_NUMBER_DIGITS * BLOCK_LENGTH, program.len()));
+    }
+
+    let mut blocks = Vec::<Block>::new();
+    for (index, chunk) in program.chunks(BLOCK_LENGTH).enumerate() {
+        let error = || format!("Block {} does not match the MONAD structure", index);
+
+        if chunk[0] != Instruction::Inp(Register::W) {
+            return Err(error());
+        }
+
+        let divisor = constant(&chunk[4]).ok_or_else(error)?;
+        let add_x = constant(&chunk[5]).ok_or_else(error)?;
+        let add_y = constant(&chunk[15]).ok_or_else(error)?;
+
+        let divides_z = match divisor {
+            1 => false,
+            26 => true,
+            _ => return Err(error()),
+        };
+
+        blocks.push(Block { divides_z, add_x, add_y });
+    }
+
+    Ok(blocks)
+}
+
+fn find_model_number(program: &[Instruction], largest: bool) -> Result<u64, String> {
+    let blocks = parse_blocks(program)?;
+    let mut digits = [0i64; MODEL_NUMBER_DIGITS];
+    let mut stack = Vec::<(usize, i64)>::new();
+
+    for (index, block) in blocks.iter().enumerate() {
+        if !block.divides_z {
+            stack.push((index, block.add_y));
+            continue;
+        }
+
+        let (pushed, add_y) = stack.pop().ok_or(format!("Block {} pops from an empty stack", index))?;
+        let difference = add_y + block.add_x;
+
+        let first = if largest {
+            9.min(9 - difference)
+        } else {
+            1.max(1 - difference)
+        };
+        let second = first + difference;
+
+        if !(1..=9).contains(&first) || !(1..=9).contains(&second) {
+            return Err(format!("No digits satisfy blocks {} and {}", pushed, index));
+        }
+
+        digits[pushed] = first;
+        digits[index] = second;
+    }
+
+    if !stack.is_empty() {
+        return Err(String::from("MONAD leaves values on the stack"));
+    }
+
+    if !is_accepted(program, &digits)? {
+        return Err(String::from("Derived model number was rejected by MONAD"));
+    }
+
+    Ok(digits.iter().fold(0, |number, digit| number * 10 + *digit as u64))
+}
+
+fn part_one(input: &str) -> u64 {
+    let program = parse_program(input).expect("Error parsing program");
+
+    find_model_number(&program, true).expect("Error finding largest model number")
+}
+
+fn part_two(input: &str) -> u64 {
+    let program = parse_program(input).expect("Error parsing program");
+
+    find_model_number(&program, false).expect("Error finding smallest model number")
+}
+
+fn main() {
+    let mut buffer = String::new();
+    io::stdin().read_to_string(&mut buffer).expect("Error reading input");
+
+    let result = part_one(&buffer);
+    println!("Day 24 part one: {}", result);
+
+    let result = part_two(&buffer);
+    println!("Day 24 part two: {}", result);
+}
+
+#[cfg(test)]
+fn generate_monad(parameters: &[(i64, i64, i64)]) -> String {
+    let mut program = String::new();
+
+    for (divisor, add_x, add_y) in parameters {
+        program.push_str(&format!("inp w
+mul x 0
+add x z
+mod x 26
+div z {}
+add x {}
+eql x w
+eql x 0
+mul y 0
+add y 25
+mul y x
+add y 1
+mul z y
+mul y 0
+add y w
+add y {}
+mul y x
+add z y
+", divisor, add_x, add_y));
+    }
+
+    program
+}
+
+#[cfg(test)]
+const TEST_PARAMETERS: [(i64, i64, i64); MODEL_NUMBER_DIGITS] = [
+    (1, 12, 4), (1, 11, 11), (1, 13, 5), (26, -6, 1), (1, 10, 2), (26, -1, 7), (26, -12, 3),
+    (1, 14, 0), (26, -3, 9), (1, 15, 7), (1, 11, 1), (26, -4, 6), (26, -9, 2), (26, -2, 8),
+];
+
+#[test]
+fn test_parse_instruction() {
+    let instruction: Instruction = String::from("add z w").parse().unwrap();
+    assert_eq!(Instruction::Add(Register::Z, Operand::Register(Register::W)), instruction);
+
+    let instruction: Instruction = String::from("mod x -26").parse().unwrap();
+    assert_eq!(Instruction::Mod(Register::X, Operand::Value(-26)), instruction);
+
+    let instruction: Instruction = String::from("inp w").parse().unwrap();
+    assert_eq!(Instruction::Inp(Register::W), instruction);
+
+    assert!("sub x 1".parse::<Instruction>().is_err());
+    assert!("add q 1".parse::<Instruction>().is_err());
+    assert!("add x".parse::<Instruction>().is_err());
+    assert!("inp w 3".parse::<Instruction>().is_err());
+}
+
+#[test]
+fn test_negate_program() {
+    let program = parse_program("inp x\nmul x -1").unwrap();
+    let mut alu = Alu::new();
+    alu.run(&program, &[7]).unwrap();
+    assert_eq!(-7, alu.get(Register::X));
+}
+
+#[test]
+fn test_three_times_program() {
+    let program = parse_program("inp z\ninp x\nmul z 3\neql z x").unwrap();
+
+    let mut alu = Alu::new();
+    alu.run(&program, &[3, 9]).unwrap();
+    assert_eq!(1, alu.get(Register::Z));
+
+    let mut alu = Alu::new();
+    alu.run(&program, &[3, 8]).unwrap();
+    assert_eq!(0, alu.get(Register::Z));
+}
+
+#[test]
+fn test_binary_program() {
+    let program = parse_program("inp w
+    add z w
+    mod z 2
+    div w 2
+    add y w
+    mod y 2
+    div w 2
+    add x w
+    mod x 2
+    div w 2
+    mod w 2").unwrap();
+
+    let mut alu = Alu::new();
+    alu.run(&program, &[11]).unwrap();
+    assert_eq!([1, 0, 1, 1], alu.registers);
+}
+
+#[test]
+fn test_alu_errors() {
+    let mut alu = Alu::new();
+    assert!(alu.run(&parse_program("div x 0").unwrap(), &[]).is_err());
+
+    let mut alu = Alu::new();
+    assert!(alu.run(&parse_program("add x -3\nmod x 2").unwrap(), &[]).is_err());
+
+    let mut alu = Alu::new();
+    assert!(alu.run(&parse_program("mod x 0").unwrap(), &[]).is_err());
+
+    let mut alu = Alu::new();
+    assert!(alu.run(&parse_program("inp x\ninp y").unwrap(), &[1]).is_err());
+
+    let mut alu = Alu::new();
+    let error = alu.run(&parse_program("inp x\nmul x 2").unwrap(), &[i64::MAX]).unwrap_err();
+    assert!(error.starts_with("Overflow multiplying"));
+
+    let mut alu = Alu::new();
+    let error = alu.run(&parse_program("inp x\nadd x 1").unwrap(), &[i64::MAX]).unwrap_err();
+    assert!(error.starts_with("Overflow adding"));
+
+    let mut alu = Alu::new();
+    let error = alu.run(&parse_program("inp x\ndiv x -1").unwrap(), &[i64::MIN]).unwrap_err();
+    assert!(error.starts_with("Overflow dividing"));
+}
+
+#[test]
+fn test_parse_blocks() {
+    let program = parse_program(&generate_monad(&TEST_PARAMETERS)).unwrap();
+    let blocks = parse_blocks(&program).unwrap();
+
+    assert_eq!(MODEL_NUMBER_DIGITS, blocks.len());
+    assert!(!blocks[0].divides_z);
+    assert!(blocks[3].divides_z);
+    assert_eq!(-6, blocks[3].add_x);
+    assert_eq!(11, blocks[1].add_y);
+
+    assert!(parse_blocks(&program[1..]).is_err());
+}
+
+#[test]
+fn test_model_number_validation() {
+    let program = parse_program(&generate_monad(&TEST_PARAMETERS)).unwrap();
+
+    assert!(is_accepted(&program, &[7, 9, 9, 8, 8, 9, 8, 9, 6, 9, 9, 6, 7, 9]).unwrap());
+    assert!(!is_accepted(&program, &[9; MODEL_NUMBER_DIGITS]).unwrap());
+}
+
+#[test]
+fn test_part_one() {
+    let input = generate_monad(&TEST_PARAMETERS);
+    assert_eq!(79988989699679, part_one(&input));
+}
+
+#[test]
+fn test_part_two() {
+    let input = generate_monad(&TEST_PARAMETERS);
+    assert_eq!(12211214134113, part_two(&input));
+}
@@ -0,0 +1,224 @@
+use std::io::{self, Read};
+use std::str::FromStr;
+use std::env;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Cell {
+    Empty,
+    East,
+    South,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct SeaFloor {
+    cells: Vec<Vec<Cell>>,
+}
+
+impl FromStr for SeaFloor {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cells = Vec::<Vec<Cell>>::new();
+
+        for line in s.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut row = Vec::<Cell>::new();
+            for c in line.chars() {
+                match c {
+                    '.' => row.push(Cell::Empty),
+                    '>' => row.push(Cell::East),
+                    'v' => row.push(Cell::South),
+                    _ => return Err(format!("Could not parse sea floor cell: {}", c)),
+                }
+            }
+
+            if let Some(first) = cells.first() {
+                if first.len() != row.len() {
+                    return Err(format!("Sea floor rows must all be the same width: {}", line));
+                }
+            }
+
+            cells.push(row);
+        }
+
+        Ok(SeaFloor { cells })
+    }
+}
+
+impl fmt::Display for SeaFloor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in &self.cells {
+            for cell in row {
+                match cell {
+                    Cell::Empty => write!(f, ".")?,
+                    Cell::East => write!(f, ">")?,
+                    Cell::South => write!(f, "v")?,
+                }
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SeaFloor {
+    // Every cucumber in a herd decides whether it can move before any of
+    // them do, and the east herd moves before the south herd looks.
+    fn move_herd(&mut self, herd: Cell) -> usize {
+        let height = self.cells.len();
+        let width = self.cells.first().map_or(0, |row| row.len());
+        let mut moves = Vec::<(usize, usize, usize, usize)>::new();
+
+        for row in 0..height {
+            for col in 0..width {
+                if self.cells[row][col] != herd {
+                    continue;
+                }
+
+                let (next_row, next_col) = match herd {
+                    Cell::East => (row, (col + 1) % width),
+                    _ => ((row + 1) % height, col),
+                };
+
+                if self.cells[next_row][next_col] == Cell::Empty {
+                    moves.push((row, col, next_row, next_col));
+                }
+            }
+        }
+
+        for (row, col, next_row, next_col) in &moves {
+            self.cells[*row][*col] = Cell::Empty;
+            self.cells[*next_row][*next_col] = herd;
+        }
+
+        moves.len()
+    }
+
+    fn step(&mut self) -> usize {
+        self.move_herd(Cell::East) + self.move_herd(Cell::South)
+    }
+
+    // Runs until nothing moves, handing every frame (including the starting
+    // one) to `on_frame`, and returns the first step on which nothing moved.
+    fn simulate_until_stationary(&mut self, mut on_frame: impl FnMut(usize, &SeaFloor)) -> usize {
+        on_frame(0, self);
+
+        let mut step = 0;
+        loop {
+            step += 1;
+            let moved = self.step();
+            on_frame(step, self);
+
+            if moved == 0 {
+                return step;
+            }
+        }
+    }
+}
+
+fn part_one(input: &str, on_frame: impl FnMut(usize, &SeaFloor)) -> usize {
+    let mut sea_floor: SeaFloor = input.parse().expect("Error parsing sea floor");
+
+    sea_floor.simulate_until_stationary(on_frame)
+}
+
+fn main() {
+    let mut buffer = String::new();
+    io::stdin().read_to_string(&mut buffer).expect("Error reading input");
+
+    let show_frames = env::args().any(|arg| arg == "--frames");
+
+    let result = part_one(&buffer, |step, frame| {
+        if show_frames {
+            println!("After {} steps:", step);
+            println!("{}", frame);
+        }
+    });
+    println!("Day 25 part one: {}", result);
+}
+
+#[test]
+fn test_parse_and_display() {
+    let sea_floor: SeaFloor = String::from("..>\nv..\n").parse().unwrap();
+    assert_eq!(Cell::East, sea_floor.cells[0][2]);
+    assert_eq!(Cell::South, sea_floor.cells[1][0]);
+    assert_eq!("..>\nv..\n", sea_floor.to_string());
+
+    assert!("..x".parse::<SeaFloor>().is_err());
+    assert!("..\n...".parse::<SeaFloor>().is_err());
+}
+
+#[test]
+fn test_east_herd_moves_together() {
+    let mut sea_floor: SeaFloor = String::from("...>>>>>...").parse().unwrap();
+
+    assert_eq!(1, sea_floor.step());
+    assert_eq!("...>>>>.>..\n", sea_floor.to_string());
+
+    assert_eq!(2, sea_floor.step());
+    assert_eq!("...>>>.>.>.\n", sea_floor.to_string());
+}
+
+#[test]
+fn test_herds_wrap_and_take_turns() {
+    let mut sea_floor: SeaFloor = String::from("..........
+    .>v....v..
+    .......>..
+    ..........").parse().unwrap();
+
+    sea_floor.step();
+    assert_eq!("..........\n.>........\n..v....v>.\n..........\n", sea_floor.to_string());
+
+    let mut sea_floor: SeaFloor = String::from("...>...
+    .......
+    ......>
+    v.....>
+    ......>
+    .......
+    ..vvv..").parse().unwrap();
+
+    sea_floor.step();
+    assert_eq!("..vv>..\n.......\n>......\nv.....>\n>......\n.......\n....v..\n", sea_floor.to_string());
+}
+
+#[test]
+fn test_frames() {
+    let input = String::from("v...>>.vv>
+    .vv>>.vv..
+    >>.>v>...v
+    >>v>>.>.v.
+    v>v.vv.v..
+    >.>>..v...
+    .vv..>.>v.
+    v.v..>>v.v
+    ....v..v.>");
+    let mut sea_floor: SeaFloor = input.parse().unwrap();
+    let mut frames = Vec::<(usize, String)>::new();
+
+    let step = sea_floor.simulate_until_stationary(|step, frame| frames.push((step, frame.to_string())));
+
+    assert_eq!(59, frames.len());
+    assert_eq!(step, frames[frames.len() - 1].0);
+    assert_eq!("v...>>.vv>", frames[0].1.lines().next().unwrap());
+    assert_eq!(frames[frames.len() - 1].1, frames[frames.len() - 2].1);
+    assert_eq!("..>>v>vv..", frames[frames.len() - 1].1.lines().next().unwrap());
+}
+
+#[test]
+fn test_part_one() {
+    let input = String::from("v...>>.vv>
+    .vv>>.vv..
+    >>.>v>...v
+    >>v>>.>.v.
+    v>v.vv.v..
+    >.>>..v...
+    .vv..>.>v.
+    v.v..>>v.v
+    ....v..v.>");
+    assert_eq!(58, part_one(&input, |_, _| ()));
+}
@@ -1,198 +1,824 @@
-use std::{io::{self, Read}, collections::HashMap};
+use std::io::{self, Read};
+use std::str::FromStr;
+use std::ops::Mul;
+use std::fmt;
+use std::env;
+use std::cmp;
+use std::process;
+
+// Unsigned integer of any size, stored as little-endian 64-bit words with no
+// trailing zero words so that equal values compare equal.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct BigUint {
+    words: Vec<u64>,
+}
 
-fn part_one(input: &str) -> i32 {
-    let mut data = HashMap::<usize, (i32, i32)>::new();
+impl From<u64> for BigUint {
+    fn from(value: u64) -> Self {
+        let mut big = BigUint { words: vec![value] };
+        big.normalise();
+        big
+    }
+}
 
-    for line in input.lines() {
-        let bit_string = line.trim();
+impl BigUint {
+    fn normalise(&mut self) {
+        while self.words.last() == Some(&0) {
+            self.words.pop();
+        }
+    }
 
-        for (i, bit) in bit_string.chars().into_iter().enumerate() {
-            let counts = data.entry(i).or_insert((0,0));
+    fn bit(&self, index: usize) -> bool {
+        self.words.get(index / 64).is_some_and(|word| word & (1 << (index % 64)) != 0)
+    }
 
-            match bit {
-                '1' => *counts = (counts.0, counts.1 + 1),
-                '0' => *counts = (counts.0 + 1, counts.1),
-                _ => panic!("Unable to process bit: {}", bit)
-            }
+    fn set_bit(&mut self, index: usize) {
+        if self.words.len() <= index / 64 {
+            self.words.resize(index / 64 + 1, 0);
         }
+        self.words[index / 64] |= 1 << (index % 64);
     }
 
-    let mut bit_string_one = String::new();
-    let mut bit_string_two = String::new();
+    // Indices of the set bits, lowest first.
+    fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(position, word)| {
+            let mut bits = *word;
+            std::iter::from_fn(move || {
+                if bits == 0 {
+                    return None;
+                }
+                let index = position * 64 + bits.trailing_zeros() as usize;
+                bits &= bits - 1;
+                Some(index)
+            })
+        })
+    }
 
-    let mut index = 0;
-    while index < data.keys().len() {
-        let counts = data.get(&index).unwrap();
+    // Divides in place and returns the remainder.
+    fn div_rem_small(&mut self, divisor: u64) -> u64 {
+        let mut remainder: u128 = 0;
 
-        if counts.0 > counts.1 {
-            bit_string_one.push_str("0");
-            bit_string_two.push_str("1");
-        } else {
-            bit_string_one.push_str("1");
-            bit_string_two.push_str("0");
+        for word in self.words.iter_mut().rev() {
+            let current = (remainder << 64) | *word as u128;
+            *word = (current / divisor as u128) as u64;
+            remainder = current % divisor as u128;
         }
 
-        index +=1;
+        self.normalise();
+        remainder as u64
     }
+}
 
-    let gamma = convert_bit_string_to_int(&bit_string_one);
-    let epsilon = convert_bit_string_to_int(&bit_string_two);
+impl Mul for &BigUint {
+    type Output = BigUint;
+    fn mul(self, other: &BigUint) -> BigUint {
+        let mut words = vec![0u64; self.words.len() + other.words.len()];
+
+        for (i, a) in self.words.iter().enumerate() {
+            let mut carry: u128 = 0;
+            for (j, b) in other.words.iter().enumerate() {
+                let current = words[i + j] as u128 + *a as u128 * *b as u128 + carry;
+                words[i + j] = current as u64;
+                carry = current >> 64;
+            }
+            words[i + other.words.len()] = carry as u64;
+        }
 
-    gamma * epsilon
+        let mut product = BigUint { words };
+        product.normalise();
+        product
+    }
 }
 
-fn convert_bit_string_to_int(bit_string: &str) -> i32 {
-    let mut result = 0;
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
 
-    let mut power = 0;
-    for bit in bit_string.chars().rev() {
-        if bit == '1' {
-            result += i32::pow(2, power);
+        let mut value = self.clone();
+        let mut chunks = Vec::<u64>::new();
+        while !value.words.is_empty() {
+            chunks.push(value.div_rem_small(CHUNK));
+        }
+
+        match chunks.pop() {
+            None => write!(f, "0"),
+            Some(first) => {
+                write!(f, "{}", first)?;
+                for chunk in chunks.iter().rev() {
+                    write!(f, "{:019}", chunk)?;
+                }
+                Ok(())
+            }
         }
-        power += 1;
     }
+}
 
-    result
+// Every line of the report is packed into a BigUint once, with the first
+// character of the line in the most significant of the `width` bits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Report {
+    width: usize,
+    values: Vec<BigUint>,
 }
 
-fn part_two(input: &str) -> i32 {
-    let mut oxygen_values = Vec::<&str>::new();
-    let mut scrubber_values = Vec::<&str>::new();
+// Lines shorter than the widest one are either rejected or treated as numbers
+// written without their leading zeros.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RaggedLines {
+    Reject,
+    PadLeft,
+}
 
-    for line in input.lines() {
-        let bit_string = line.trim();
-        oxygen_values.push(&bit_string);
-        scrubber_values.push(&bit_string);
+impl FromStr for Report {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Report::parse(s, RaggedLines::Reject)
     }
+}
 
-    let oxygen_int = compute_oxgyen_value(oxygen_values);
-    let scrubber_int = compute_scrubber_value(scrubber_values);
+fn parse_bits(bit_string: &str) -> Result<BigUint, String> {
+    let mut value = BigUint::default();
+    let width = bit_string.chars().count();
+
+    for (column, bit) in bit_string.chars().enumerate() {
+        match bit {
+            '1' => value.set_bit(width - 1 - column),
+            '0' => (),
+            _ => return Err(format!("column {}: unable to process bit: {:?}", column + 1, bit)),
+        }
+    }
 
-    oxygen_int * scrubber_int
+    Ok(value)
 }
 
-fn compute_oxgyen_value(oxgyen_bit_strings: Vec<&str>) -> i32 {
-    let mut oxygen_values = oxgyen_bit_strings.clone();
-    let mut bit_index = 0;
-    while bit_index < oxygen_values.first().unwrap().len() {
-        let mut ones = 0;
-        let mut zeros = 0;
+impl Report {
+    fn parse(input: &str, ragged: RaggedLines) -> Result<Report, String> {
+        let mut width = 0;
+        let mut first_line = 0;
+        let mut values = Vec::<BigUint>::new();
+
+        for (line_index, line) in input.lines().enumerate() {
+            let bit_string = line.trim();
+            if bit_string.is_empty() {
+                continue;
+            }
+
+            let line_width = bit_string.chars().count();
+            if values.is_empty() {
+                width = line_width;
+                first_line = line_index + 1;
+            } else if line_width != width {
+                match ragged {
+                    RaggedLines::Reject => return Err(format!("Line {}: expected {} bits like line {} but found {}", line_index + 1, width, first_line, line_width)),
+                    RaggedLines::PadLeft => width = width.max(line_width),
+                }
+            }
+
+            values.push(parse_bits(bit_string).map_err(|error| format!("Line {}, {}", line_index + 1, error))?);
+        }
+
+        if values.is_empty() {
+            return Err(String::from("Diagnostic report is empty"));
+        }
+
+        Ok(Report { width, values })
+    }
+
+    // Columns are numbered from the left, so column 0 is the most significant bit.
+    fn bit_index(&self, column: usize) -> usize {
+        self.width - 1 - column
+    }
 
-        for bit_string in &oxygen_values {
-            let bit = bit_string.chars().nth(bit_index).unwrap();
-            match bit {
-                '1' => ones += 1,
-                '0' => zeros += 1,
-                _ => panic!("Could not parse bit: {}", bit)
+    // Returns (zeros, ones) for every column, visiting only the set bits of each value.
+    fn column_counts(&self) -> Vec<(usize, usize)> {
+        let mut ones = vec![0; self.width];
+
+        for value in &self.values {
+            for index in value.ones() {
+                ones[self.width - 1 - index] += 1;
             }
         }
 
-        if ones >= zeros {
-            oxygen_values = remove_values_for_common_bit(bit_index, '1', oxygen_values);
-        } else {
-            oxygen_values = remove_values_for_common_bit(bit_index, '0', oxygen_values);
+        ones.into_iter().map(|count| (self.values.len() - count, count)).collect()
+    }
+
+    fn gamma(&self) -> BigUint {
+        let mut gamma = BigUint::default();
+
+        for (column, (zeros, ones)) in self.column_counts().into_iter().enumerate() {
+            if ones >= zeros {
+                gamma.set_bit(self.bit_index(column));
+            }
         }
 
-        if oxygen_values.len() == 1 {
-            break;
+        gamma
+    }
+
+    fn epsilon(&self) -> BigUint {
+        let gamma = self.gamma();
+        let mut epsilon = BigUint::default();
+
+        for index in 0..self.width {
+            if !gamma.bit(index) {
+                epsilon.set_bit(index);
+            }
         }
 
-        bit_index += 1;
+        epsilon
     }
 
-    let oxygen_bit_string = oxygen_values.first().unwrap();
+}
+
+#[derive(Clone, Debug)]
+struct TrieNode {
+    count: usize,
+    children: [Option<usize>; 2],
+}
 
-    convert_bit_string_to_int(oxygen_bit_string)
+// Binary trie over the report values, most significant bit first, where each
+// node counts the values below it. Filtering for a rating is a single walk
+// from the root, so both ratings cost O(width) after an O(total bits) build.
+struct BitTrie {
+    width: usize,
+    nodes: Vec<TrieNode>,
 }
 
-fn compute_scrubber_value(srubber_bit_strings: Vec<&str>) -> i32 {
-    let mut scrubber_values = srubber_bit_strings.clone();
-    let mut bit_index = 0;
-    while bit_index < scrubber_values.first().unwrap().len() {
-        let mut ones = 0;
-        let mut zeros = 0;
+impl BitTrie {
+    fn build(report: &Report) -> BitTrie {
+        let mut nodes = vec![TrieNode { count: 0, children: [None, None] }];
+
+        for value in &report.values {
+            let mut node = 0;
+            nodes[node].count += 1;
+
+            for column in 0..report.width {
+                let bit = value.bit(report.bit_index(column)) as usize;
+                node = match nodes[node].children[bit] {
+                    Some(child) => child,
+                    None => {
+                        nodes.push(TrieNode { count: 0, children: [None, None] });
+                        let child = nodes.len() - 1;
+                        nodes[node].children[bit] = Some(child);
+                        child
+                    }
+                };
+                nodes[node].count += 1;
+            }
+        }
+
+        BitTrie { width: report.width, nodes }
+    }
+
+    fn child_count(&self, node: usize, bit: bool) -> usize {
+        self.nodes[node].children[bit as usize].map_or(0, |child| self.nodes[child].count)
+    }
+
+    fn filter_rating(&self, criterion: Criterion, tie_policy: TiePolicy) -> Result<BigUint, String> {
+        self.walk_rating(criterion, tie_policy, |_| ())
+    }
+
+    // Walks down the trie column by column, following the branch whose bit
+    // matches the criterion and handing each decision to `on_step`. Once a
+    // single value is left its remaining bits are read off the only path below.
+    fn walk_rating(&self, criterion: Criterion, tie_policy: TiePolicy, mut on_step: impl FnMut(FilterStep)) -> Result<BigUint, String> {
+        let mut node = 0;
+        let mut value = BigUint::default();
+
+        if self.nodes[node].count == 0 {
+            return Err(String::from("No values to filter"));
+        }
 
-        for bit_string in &scrubber_values {
-            let bit = bit_string.chars().nth(bit_index).unwrap();
-            match bit {
-                '1' => ones += 1,
-                '0' => zeros += 1,
-                _ => panic!("Could not parse bit: {}", bit)
+        for column in 0..self.width {
+            let zeros = self.child_count(node, false);
+            let ones = self.child_count(node, true);
+
+            let keep_ones = if self.nodes[node].count == 1 {
+                ones == 1
+            } else if ones == zeros {
+                match tie_policy {
+                    TiePolicy::PreferOne => true,
+                    TiePolicy::PreferZero => false,
+                    TiePolicy::Error => return Err(format!("Column {} is tied with {} candidates left", column + 1, self.nodes[node].count)),
+                }
+            } else {
+                match criterion {
+                    Criterion::MostCommon => ones > zeros,
+                    Criterion::LeastCommon => ones < zeros,
+                }
+            };
+
+            let counts = ColumnStats { zeros, ones };
+            node = match self.nodes[node].children[keep_ones as usize] {
+                Some(child) => child,
+                None => {
+                    on_step(FilterStep { counts, kept: keep_ones as u8, survivors: 0 });
+                    return Err(format!(
+                        "No values survived filtering: all {} candidates left at column {} have bit {}",
+                        self.nodes[node].count, column + 1, !keep_ones as u8
+                    ));
+                }
+            };
+            if keep_ones {
+                value.set_bit(self.width - 1 - column);
             }
+            on_step(FilterStep { counts, kept: keep_ones as u8, survivors: self.nodes[node].count });
+        }
+
+        Ok(value)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Criterion {
+    MostCommon,
+    LeastCommon,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TiePolicy {
+    PreferOne,
+    PreferZero,
+    Error,
+}
+
+// A rating is written as `criterion:tie`, e.g. `most:one` for the oxygen
+// generator rating or `least:error` to reject any tied column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Rating {
+    criterion: Criterion,
+    tie_policy: TiePolicy,
+}
+
+const OXYGEN_RATING: Rating = Rating { criterion: Criterion::MostCommon, tie_policy: TiePolicy::PreferOne };
+const SCRUBBER_RATING: Rating = Rating { criterion: Criterion::LeastCommon, tie_policy: TiePolicy::PreferZero };
+
+impl FromStr for Rating {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (criterion, tie_policy) = s.split_once(':').ok_or(format!("Rating must be criterion:tie: {}", s))?;
+
+        let criterion = match criterion {
+            "most" => Criterion::MostCommon,
+            "least" => Criterion::LeastCommon,
+            _ => return Err(format!("Criterion must be most or least: {}", criterion)),
+        };
+
+        let tie_policy = match tie_policy {
+            "one" => TiePolicy::PreferOne,
+            "zero" => TiePolicy::PreferZero,
+            "error" => TiePolicy::Error,
+            _ => return Err(format!("Tie policy must be one, zero or error: {}", tie_policy)),
+        };
+
+        Ok(Rating { criterion, tie_policy })
+    }
+}
+
+impl Rating {
+    fn compute(&self, trie: &BitTrie) -> Result<BigUint, String> {
+        trie.filter_rating(self.criterion, self.tie_policy)
+    }
+}
+
+fn part_one(report: &Report) -> BigUint {
+    &report.gamma() * &report.epsilon()
+}
+
+fn part_two(trie: &BitTrie) -> Result<BigUint, String> {
+    let oxygen = compute_oxygen_value(trie).map_err(|error| format!("Oxygen generator rating: {}", error))?;
+    let scrubber = compute_scrubber_value(trie).map_err(|error| format!("CO2 scrubber rating: {}", error))?;
+
+    Ok(&oxygen * &scrubber)
+}
+
+fn compute_oxygen_value(trie: &BitTrie) -> Result<BigUint, String> {
+    OXYGEN_RATING.compute(trie)
+}
+
+fn compute_scrubber_value(trie: &BitTrie) -> Result<BigUint, String> {
+    SCRUBBER_RATING.compute(trie)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ColumnStats {
+    zeros: usize,
+    ones: usize,
+}
+
+impl ColumnStats {
+    fn majority(&self) -> Option<u8> {
+        match self.ones.cmp(&self.zeros) {
+            cmp::Ordering::Greater => Some(1),
+            cmp::Ordering::Less => Some(0),
+            cmp::Ordering::Equal => None,
+        }
+    }
+
+    fn margin(&self) -> usize {
+        self.ones.abs_diff(self.zeros)
+    }
+}
+
+// One column of a rating filter: how the candidates still left split on that
+// column, the bit that was kept and how many candidates have it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct FilterStep {
+    counts: ColumnStats,
+    kept: u8,
+    survivors: usize,
+}
+
+// The whole-report counts behind gamma and epsilon, and the steps each rating
+// filter took. A filter that fails stops early, so its list can be shorter
+// than the report is wide.
+struct ColumnReport {
+    report: Vec<ColumnStats>,
+    oxygen: Vec<FilterStep>,
+    scrubber: Vec<FilterStep>,
+}
+
+fn rating_steps(trie: &BitTrie, rating: Rating) -> Vec<FilterStep> {
+    let mut steps = Vec::<FilterStep>::new();
+    let _ = trie.walk_rating(rating.criterion, rating.tie_policy, |step| steps.push(step));
+
+    steps
+}
+
+fn json_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl ColumnReport {
+    fn build(report: &Report, trie: &BitTrie) -> ColumnReport {
+        ColumnReport {
+            report: report.column_counts().into_iter().map(|(zeros, ones)| ColumnStats { zeros, ones }).collect(),
+            oxygen: rating_steps(trie, OXYGEN_RATING),
+            scrubber: rating_steps(trie, SCRUBBER_RATING),
         }
+    }
+
+    fn to_text(&self) -> String {
+        let majority = |counts: &ColumnStats| counts.majority().map_or(String::from("tie"), |bit| bit.to_string());
 
-        if ones >= zeros {
-            scrubber_values = remove_values_for_common_bit(bit_index, '0', scrubber_values);
-        } else {
-            scrubber_values = remove_values_for_common_bit(bit_index, '1', scrubber_values);
+        let mut text = String::from("report\ncolumn  zeros  ones  majority  margin\n");
+        for (column, counts) in self.report.iter().enumerate() {
+            text.push_str(&format!(
+                "{:>6}  {:>5}  {:>4}  {:>8}  {:>6}\n",
+                column + 1, counts.zeros, counts.ones, majority(counts), counts.margin()
+            ));
         }
 
-        if scrubber_values.len() == 1 {
-            break;
+        for (name, steps) in [("oxygen generator rating", &self.oxygen), ("CO2 scrubber rating", &self.scrubber)] {
+            text.push_str(&format!("{}\ncolumn  zeros  ones  majority  margin  kept  survivors\n", name));
+            for (column, step) in steps.iter().enumerate() {
+                text.push_str(&format!(
+                    "{:>6}  {:>5}  {:>4}  {:>8}  {:>6}  {:>4}  {:>9}\n",
+                    column + 1, step.counts.zeros, step.counts.ones, majority(&step.counts), step.counts.margin(), step.kept, step.survivors
+                ));
+            }
         }
 
-        bit_index += 1;
+        text
     }
 
-    let scrubber_bit_string = scrubber_values.first().unwrap();
+    fn to_json(&self, part_one: &BigUint, part_two: &Result<BigUint, String>) -> String {
+        let counts_json = |column: usize, counts: &ColumnStats| format!(
+            "\"column\":{},\"zeros\":{},\"ones\":{},\"majority\":{},\"margin\":{}",
+            column + 1,
+            counts.zeros,
+            counts.ones,
+            counts.majority().map_or(String::from("null"), |bit| bit.to_string()),
+            counts.margin()
+        );
+        let steps_json = |steps: &[FilterStep]| {
+            let steps: Vec<String> = steps
+                .iter()
+                .enumerate()
+                .map(|(column, step)| format!("{{{},\"kept\":{},\"survivors\":{}}}", counts_json(column, &step.counts), step.kept, step.survivors))
+                .collect();
+            format!("[{}]", steps.join(","))
+        };
+
+        let columns: Vec<String> = self.report.iter().enumerate().map(|(column, counts)| format!("{{{}}}", counts_json(column, counts))).collect();
+        let (part_two, error) = match part_two {
+            Ok(value) => (value.to_string(), String::from("null")),
+            Err(error) => (String::from("null"), json_string(error)),
+        };
+
+        format!(
+            "{{\"part_one\":{},\"part_two\":{},\"part_two_error\":{},\"columns\":[{}],\"oxygen\":{},\"scrubber\":{}}}",
+            part_one, part_two, error, columns.join(","), steps_json(&self.oxygen), steps_json(&self.scrubber)
+        )
+    }
+}
 
-    convert_bit_string_to_int(scrubber_bit_string)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ColumnFormat {
+    Text,
+    Json,
 }
 
+struct Options {
+    ratings: Vec<(String, Rating)>,
+    ragged: RaggedLines,
+    columns: Option<ColumnFormat>,
+}
 
-fn remove_values_for_common_bit(index: usize, bit: char, values: Vec<&str>) -> Vec<&str>{
-    let mut valid_values = Vec::<&str>::new();
-    for value in values.clone() {
-        if value.chars().nth(index).unwrap() == bit {
-            valid_values.push(value);
+fn parse_options() -> Options {
+    let mut options = Options { ratings: Vec::new(), ragged: RaggedLines::Reject, columns: None };
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--rating" => {
+                let value = args.next().expect("Missing value for --rating");
+                let rating = value.parse().expect("Error parsing rating");
+                options.ratings.push((value, rating));
+            },
+            "--ragged" => {
+                options.ragged = match args.next().as_deref() {
+                    Some("reject") => RaggedLines::Reject,
+                    Some("pad") => RaggedLines::PadLeft,
+                    other => panic!("--ragged must be reject or pad: {:?}", other),
+                }
+            },
+            "--columns" => {
+                options.columns = match args.next().as_deref() {
+                    Some("text") => Some(ColumnFormat::Text),
+                    Some("json") => Some(ColumnFormat::Json),
+                    other => panic!("--columns must be text or json: {:?}", other),
+                }
+            },
+            _ => panic!("Unknown argument: {}", arg),
         }
     }
 
-    valid_values
+    options
 }
 
 fn main() {
+    let options = parse_options();
+
     let mut buffer = String::new();
     io::stdin().read_to_string(&mut buffer).expect("Error reading input");
 
-    let result = part_one(&buffer);
-    println!("Day 3 part one: {}", result);
+    let report = Report::parse(&buffer, options.ragged).expect("Error parsing diagnostic report");
+    let trie = BitTrie::build(&report);
+
+    let part_one_result = part_one(&report);
+    let part_two_result = part_two(&trie);
+
+    if options.columns == Some(ColumnFormat::Json) {
+        if !options.ratings.is_empty() {
+            panic!("--columns json and --rating cannot be combined");
+        }
+
+        println!("{}", ColumnReport::build(&report, &trie).to_json(&part_one_result, &part_two_result));
+        if part_two_result.is_err() {
+            process::exit(1);
+        }
+        return;
+    }
+
+    println!("Day 3 part one: {}", part_one_result);
+
+    let mut failed = false;
+    match &part_two_result {
+        Ok(result) => println!("Day 3 part two: {}", result),
+        Err(error) => {
+            eprintln!("Day 3 part two: {}", error);
+            failed = true;
+        }
+    }
+
+    for (name, rating) in options.ratings {
+        match rating.compute(&trie) {
+            Ok(value) => println!("Day 3 rating {}: {}", name, value),
+            Err(error) => {
+                eprintln!("Day 3 rating {}: {}", name, error);
+                failed = true;
+            }
+        }
+    }
+
+    if options.columns == Some(ColumnFormat::Text) {
+        print!("{}", ColumnReport::build(&report, &trie).to_text());
+    }
 
-    let result = part_two(&buffer);
-    println!("Day 3 part two: {}", result);
+    if failed {
+        process::exit(1);
+    }
 }
 
 #[test]
 fn test_part_one() {
-    assert_eq!(198, part_one("00100\n11110\n10110\n10111\n10101\n01111\n00111\n11100\n10000\n11001\n00010\n01010\n"));
+    assert_eq!(BigUint::from(198), part_one(&"00100\n11110\n10110\n10111\n10101\n01111\n00111\n11100\n10000\n11001\n00010\n01010\n".parse().unwrap()));
+}
+
+#[test]
+fn test_parse_bits() {
+    assert_eq!(Ok(BigUint::from(22)), parse_bits("10110"));
+    assert_eq!(Ok(BigUint::from(9)), parse_bits("01001"));
+    assert_eq!(Ok(BigUint::default()), parse_bits("0000"));
+    assert_eq!(Err(String::from("column 3: unable to process bit: '2'")), parse_bits("01201"));
 }
 
 #[test]
-fn test_convert_bit_string_to_int() {
-    assert_eq!(22, convert_bit_string_to_int("10110"));
-    assert_eq!(9, convert_bit_string_to_int("01001"));
+fn test_column_counts() {
+    let report = "00100\n11110\n10110\n10111\n10101\n01111\n00111\n11100\n10000\n11001\n00010\n01010\n".parse::<Report>().unwrap();
+    assert_eq!(5, report.width);
+    assert_eq!(vec![(5, 7), (7, 5), (4, 8), (5, 7), (7, 5)], report.column_counts());
+    assert_eq!(BigUint::from(22), report.gamma());
+    assert_eq!(BigUint::from(9), report.epsilon());
 }
 
 #[test]
-fn test_remove_values_for_common_bit() {
-    let bit_strings = vec!["00100", "11110", "10110", "10111", "10101", "01111", "00111", "11100", "10000", "11001", "00010", "01010"];
-    let result = remove_values_for_common_bit(0, '1', bit_strings);
-    assert_eq!(7, result.len());
+fn test_big_uint() {
+    assert_eq!("0", BigUint::default().to_string());
+    assert_eq!("18446744073709551615", BigUint::from(u64::MAX).to_string());
+
+    let two_to_the_64 = parse_bits(&format!("1{}", "0".repeat(64))).unwrap();
+    assert_eq!("18446744073709551616", two_to_the_64.to_string());
+    assert_eq!("340282366920938463463374607431768211456", (&two_to_the_64 * &two_to_the_64).to_string());
+
+    let all_ones = parse_bits(&"1".repeat(100)).unwrap();
+    let mut other = parse_bits(&"0".repeat(100)).unwrap();
+    assert_eq!(BigUint::default(), &all_ones * &other);
+    other.set_bit(99);
+    other.words[0] |= 12345;
+    assert_eq!("803469022129495137770981061819094135778479343667449136402375", (&all_ones * &other).to_string());
+}
+
+#[test]
+fn test_wide_report() {
+    let leading_one = format!("1{}", "0".repeat(99));
+    let report = format!("{}\n{}\n{}\n", "1".repeat(100), leading_one, "0".repeat(100)).parse::<Report>().unwrap();
+    assert_eq!(100, report.width);
+    assert_eq!(parse_bits(&leading_one).unwrap(), report.gamma());
+    assert_eq!(parse_bits(&"1".repeat(99)).unwrap(), report.epsilon());
+    assert_eq!(parse_bits(&"1".repeat(100)).unwrap(), compute_oxygen_value(&BitTrie::build(&report)).unwrap());
+    assert_eq!(BigUint::default(), compute_scrubber_value(&BitTrie::build(&report)).unwrap());
+
+    // Above 31 bits the old i32 arithmetic overflowed; the product must stay exact.
+    let input = format!("{}\n{}\n{}\n", "10".repeat(20), "11".repeat(20), "00".repeat(20));
+    let gamma: u128 = u128::from_str_radix(&"10".repeat(20), 2).unwrap();
+    let epsilon: u128 = u128::from_str_radix(&"01".repeat(20), 2).unwrap();
+    assert_eq!((gamma * epsilon).to_string(), part_one(&input.parse().unwrap()).to_string());
+}
+
+#[test]
+fn test_trie_counts() {
+    let trie = BitTrie::build(&"00100\n11110\n10110\n10111\n10101\n01111\n00111\n11100\n10000\n11001\n00010\n01010\n".parse::<Report>().unwrap());
+    assert_eq!(12, trie.nodes[0].count);
+    assert_eq!(7, trie.child_count(0, true));
+    assert_eq!(5, trie.child_count(0, false));
+
+    let ones = trie.nodes[0].children[1].unwrap();
+    assert_eq!(4, trie.child_count(ones, false));
+    assert_eq!(3, trie.child_count(ones, true));
+}
+
+#[test]
+fn test_trie_wide_reports() {
+    let sample = ["00100", "11110", "10110", "10111", "10101", "01111", "00111", "11100", "10000", "11001", "00010", "01010"];
+
+    // Repeating each sample line keeps the same decisions in the first five
+    // columns, after which a single candidate is left to follow.
+    let input: String = sample.iter().map(|line| format!("{}\n", line.repeat(14))).collect();
+    let trie = BitTrie::build(&input.parse::<Report>().unwrap());
+    assert_eq!(parse_bits(&"10111".repeat(14)), compute_oxygen_value(&trie));
+    assert_eq!(parse_bits(&"01010".repeat(14)), compute_scrubber_value(&trie));
+
+    // A shared 80-bit prefix pushes the deciding columns past the first word.
+    let prefix = "10".repeat(40);
+    let input: String = sample.iter().map(|line| format!("{}{}\n", prefix, line)).collect();
+    let trie = BitTrie::build(&input.parse::<Report>().unwrap());
+    assert_eq!(parse_bits(&format!("{}10111", prefix)), compute_oxygen_value(&trie));
+    assert_eq!(
+        Err(String::from("No values survived filtering: all 12 candidates left at column 1 have bit 1")),
+        compute_scrubber_value(&trie)
+    );
+    assert_eq!(parse_bits(&format!("{}10110", prefix)), "most:zero".parse::<Rating>().unwrap().compute(&trie));
 }
 
 #[test]
 fn test_compute_oxygen_value() {
-    let bit_strings = vec!["00100", "11110", "10110", "10111", "10101", "01111", "00111", "11100", "10000", "11001", "00010", "01010"];
-    assert_eq!(23, compute_oxgyen_value(bit_strings));
+    let trie = BitTrie::build(&"00100\n11110\n10110\n10111\n10101\n01111\n00111\n11100\n10000\n11001\n00010\n01010\n".parse::<Report>().unwrap());
+    assert_eq!(BigUint::from(23), compute_oxygen_value(&trie).unwrap());
 }
 
 #[test]
 fn test_compute_scrubber_value() {
-    let bit_strings = vec!["00100", "11110", "10110", "10111", "10101", "01111", "00111", "11100", "10000", "11001", "00010", "01010"];
-    assert_eq!(10, compute_scrubber_value(bit_strings));
+    let trie = BitTrie::build(&"00100\n11110\n10110\n10111\n10101\n01111\n00111\n11100\n10000\n11001\n00010\n01010\n".parse::<Report>().unwrap());
+    assert_eq!(BigUint::from(10), compute_scrubber_value(&trie).unwrap());
 }
 
 #[test]
 fn test_part_two() {
-    assert_eq!(230, part_two("00100\n11110\n10110\n10111\n10101\n01111\n00111\n11100\n10000\n11001\n00010\n01010\n"));
-}
\ No newline at end of file
+    assert_eq!(BigUint::from(230), part_two(&BitTrie::build(&"00100\n11110\n10110\n10111\n10101\n01111\n00111\n11100\n10000\n11001\n00010\n01010\n".parse().unwrap())).unwrap());
+}
+
+#[test]
+fn test_parse_rating() {
+    assert_eq!(Ok(OXYGEN_RATING), "most:one".parse());
+    assert_eq!(Ok(SCRUBBER_RATING), "least:zero".parse());
+    assert_eq!(Ok(Rating { criterion: Criterion::LeastCommon, tie_policy: TiePolicy::Error }), "least:error".parse());
+
+    assert!("most".parse::<Rating>().is_err());
+    assert!("middle:one".parse::<Rating>().is_err());
+    assert!("most:two".parse::<Rating>().is_err());
+}
+
+#[test]
+fn test_custom_ratings() {
+    let trie = BitTrie::build(&"00100\n11110\n10110\n10111\n10101\n01111\n00111\n11100\n10000\n11001\n00010\n01010\n".parse::<Report>().unwrap());
+
+    // Oxygen ties at 1011x and keeps the 1; preferring 0 keeps 10110 instead.
+    assert_eq!(Ok(BigUint::from(22)), "most:zero".parse::<Rating>().unwrap().compute(&trie));
+    assert!("most:error".parse::<Rating>().unwrap().compute(&trie).is_err());
+
+    // The scrubber ties at 01xxx and keeps the 0; preferring 1 keeps 01111 instead.
+    assert_eq!(Ok(BigUint::from(15)), "least:one".parse::<Rating>().unwrap().compute(&trie));
+    assert_eq!(
+        Err(String::from("Column 3 is tied with 2 candidates left")),
+        "least:error".parse::<Rating>().unwrap().compute(&trie)
+    );
+}
+
+#[test]
+fn test_report_validation() {
+    assert_eq!(
+        Err(String::from("Line 3, column 4: unable to process bit: 'x'")),
+        "00100\n11110\n101x0\n".parse::<Report>()
+    );
+    assert_eq!(
+        Err(String::from("Line 4: expected 5 bits like line 2 but found 4")),
+        "\n00100\n11110\n0110\n".parse::<Report>()
+    );
+    assert_eq!(Err(String::from("Diagnostic report is empty")), "\n  \n".parse::<Report>());
+
+    let report = Report::parse("00100\n110\n1\n", RaggedLines::PadLeft).unwrap();
+    assert_eq!(5, report.width);
+    assert_eq!(vec![BigUint::from(4), BigUint::from(6), BigUint::from(1)], report.values);
+    assert_eq!(vec![(3, 0), (3, 0), (1, 2), (2, 1), (2, 1)], report.column_counts());
+}
+
+#[test]
+fn test_no_rating_survives() {
+    // Both values agree on every column, so the least common bit is never present.
+    let trie = BitTrie::build(&"101\n101\n".parse::<Report>().unwrap());
+    assert_eq!(Ok(BigUint::from(5)), compute_oxygen_value(&trie));
+    assert_eq!(
+        Err(String::from("No values survived filtering: all 2 candidates left at column 1 have bit 1")),
+        compute_scrubber_value(&trie)
+    );
+    assert!(part_two(&trie).unwrap_err().starts_with("CO2 scrubber rating: "));
+}
+
+#[test]
+fn test_column_report() {
+    let report: Report = "00100\n11110\n10110\n10111\n10101\n01111\n00111\n11100\n10000\n11001\n00010\n01010\n".parse().unwrap();
+    let column_report = ColumnReport::build(&report, &BitTrie::build(&report));
+
+    assert_eq!(ColumnStats { zeros: 4, ones: 8 }, column_report.report[2]);
+    assert_eq!(Some(1), column_report.report[2].majority());
+    assert_eq!(4, column_report.report[2].margin());
+
+    let survivors: Vec<usize> = column_report.oxygen.iter().map(|step| step.survivors).collect();
+    assert_eq!(vec![7, 4, 3, 2, 1], survivors);
+    assert_eq!(FilterStep { counts: ColumnStats { zeros: 1, ones: 1 }, kept: 1, survivors: 1 }, column_report.oxygen[4]);
+
+    // The scrubber's third decision was a tie between 01111 and 01010, which
+    // the whole-report counts for that column do not show.
+    let survivors: Vec<usize> = column_report.scrubber.iter().map(|step| step.survivors).collect();
+    assert_eq!(vec![5, 2, 1, 1, 1], survivors);
+    assert_eq!(FilterStep { counts: ColumnStats { zeros: 1, ones: 1 }, kept: 0, survivors: 1 }, column_report.scrubber[2]);
+    assert_eq!(None, column_report.scrubber[2].counts.majority());
+
+    let text = column_report.to_text();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(3 * 7, lines.len());
+    assert_eq!("report", lines[0]);
+    assert_eq!("     3      4     8         1       4", lines[4]);
+    assert_eq!("CO2 scrubber rating", lines[14]);
+    assert_eq!("     3      1     1       tie       0     0          1", lines[18]);
+
+    let json = column_report.to_json(&BigUint::from(198), &Ok(BigUint::from(230)));
+    assert!(json.starts_with("{\"part_one\":198,\"part_two\":230,\"part_two_error\":null,\"columns\":[{\"column\":1,\"zeros\":5,\"ones\":7,\"majority\":1,\"margin\":2}"));
+    assert!(json.contains("{\"column\":3,\"zeros\":1,\"ones\":1,\"majority\":null,\"margin\":0,\"kept\":0,\"survivors\":1}"));
+    assert_eq!(15, json.matches("\"column\":").count());
+}
+
+#[test]
+fn test_column_report_after_failed_filter() {
+    let report: Report = "110\n110\n".parse().unwrap();
+    let trie = BitTrie::build(&report);
+    let column_report = ColumnReport::build(&report, &trie);
+
+    assert_eq!(3, column_report.oxygen.len());
+    assert_eq!(vec![FilterStep { counts: ColumnStats { zeros: 0, ones: 2 }, kept: 0, survivors: 0 }], column_report.scrubber);
+
+    let json = column_report.to_json(&BigUint::from(6), &part_two(&trie));
+    assert!(json.contains("\"part_two\":null,\"part_two_error\":\"CO2 scrubber rating: No values survived filtering: all 2 candidates left at column 1 have bit 1\""));
+    assert_eq!("\"say \\\"hi\\\"\"", json_string("say \"hi\""));
+}